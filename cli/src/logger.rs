@@ -11,7 +11,6 @@ pub async fn setup() -> Result<()> {
 
     let log_file = std::fs::OpenOptions::new()
         .create(true)
-        .write(true)
         .append(true)
         .open(log_path)?;
 
@@ -1,10 +1,13 @@
 use clap::{Parser, Subcommand};
-use eyre::Result;
+use eyre::{eyre, Result};
+use std::path::PathBuf;
+use tokio::sync::broadcast::error::RecvError;
 
 mod logger;
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
+#[allow(clippy::upper_case_acronyms)]
 struct CLI {
     #[command(subcommand)]
     command: Commands,
@@ -22,24 +25,116 @@ enum Commands {
     },
     Launch {
         name: String,
+        #[arg(short, long)]
+        wait: bool,
+        #[arg(long)]
+        safe: bool,
+        #[arg(long, conflicts_with = "safe")]
+        vanilla: bool,
+    },
+    Unlink {
+        id: String,
+    },
+    LaunchOptions {
+        id: String,
+        #[arg(long, allow_hyphen_values = true)]
+        args: Option<String>,
+        #[arg(long, value_name = "KEY=VALUE")]
+        env: Vec<String>,
+        #[arg(long)]
+        clear_env: bool,
+        #[arg(long, allow_hyphen_values = true)]
+        wrapper: Option<String>,
+        #[arg(long)]
+        game_dir: Option<PathBuf>,
+        #[arg(long, requires = "game_dir")]
+        exe: Option<PathBuf>,
+        #[arg(long, conflicts_with_all = ["game_dir", "exe"])]
+        clear_game_dir: bool,
+    },
+    List {
+        #[arg(short, long)]
+        recent: bool,
+    },
+    Diagnose {
+        id: String,
+    },
+    Bisect {
+        id: String,
+        #[arg(long)]
+        abort: bool,
+    },
+    Logs {
+        id: String,
+        session: Option<String>,
+        #[arg(short, long)]
+        player: bool,
     },
-    List,
     AddPlugin {
         id: String,
         url: String,
     },
+    Autoremove {
+        id: String,
+    },
+    Update {
+        id: String,
+        plugin: String,
+        #[arg(long)]
+        version: Option<String>,
+    },
+    Clone {
+        id: String,
+        new_name: String,
+    },
     Export {
         id: String,
     },
     Import {
         path: String,
     },
+    Lock {
+        id: String,
+    },
+    Sync {
+        path: String,
+    },
+    Apply {
+        path: String,
+        #[arg(long)]
+        dry_run: bool,
+    },
+    ResetConfig {
+        id: String,
+        plugin: String,
+    },
     Remove {
         id: String,
     },
+    Rename {
+        id: String,
+        new_name: String,
+    },
     Shortcut {
         id: String,
-    }
+        #[arg(long)]
+        remove: bool,
+    },
+    SteamShortcut {
+        id: String,
+    },
+    Snapshot {
+        id: String,
+        #[arg(short, long)]
+        label: Option<String>,
+    },
+    Snapshots {
+        id: String,
+    },
+    Restore {
+        id: String,
+        snapshot: i64,
+    },
 }
 
 #[tokio::main]
@@ -47,9 +142,11 @@ async fn main() -> Result<()> {
     logger::setup().await?;
     manager::event::EventState::init().await?;
 
+    print_events()?;
+
     let cli = CLI::parse();
 
-    match &cli.command {
+    match cli.command {
         Commands::Target { slug } => {
             if let Some(slug) = slug {
                 println!("{:#?}", manager::specific_target(&slug));
@@ -60,30 +157,263 @@ async fn main() -> Result<()> {
         Commands::Create { name, game } => {
             manager::create_collection(&name, &game).await?;
         }
-        Commands::Launch { name } => {
-            manager::launch_collection_detached(&name).await?;
+        Commands::Launch { name, vanilla: true, .. } => {
+            manager::launch_vanilla(&name).await?;
+        }
+        Commands::Launch { name, safe: true, .. } => {
+            manager::launch_collection_safe(&name).await?;
+
+            println!("Plugins have been restored");
+        }
+        Commands::Launch { name, wait, .. } => {
+            if wait {
+                let session = manager::launch_collection_supervised(&name).await?;
+
+                println!("Played {} for {} minutes", name, session.play_time_secs() / 60);
+            } else {
+                manager::launch_collection_detached(&name).await?;
+            }
+        }
+        Commands::LaunchOptions { id, args, env, clear_env, wrapper, game_dir, exe, clear_game_dir } => {
+            let mut options = manager::launch_options(&id).await?;
+            let is_edit = args.is_some()
+                || !env.is_empty()
+                || clear_env
+                || wrapper.is_some()
+                || game_dir.is_some()
+                || clear_game_dir;
+
+            if let Some(args) = args {
+                options.args = split_command_line(&args)?;
+            }
+
+            if clear_env {
+                options.env.clear();
+            }
+
+            for variable in env {
+                let (key, value) = variable
+                    .split_once('=')
+                    .ok_or_else(|| eyre!("environment variable '{}' must be KEY=VALUE", variable))?;
+
+                options.env.insert(key.to_owned(), value.to_owned());
+            }
+
+            if let Some(wrapper) = wrapper {
+                options.wrapper = split_command_line(&wrapper)?;
+            }
+
+            if clear_game_dir {
+                options.game_dir = None;
+                options.executable = None;
+            }
+
+            if let Some(game_dir) = game_dir {
+                options.game_dir = Some(std::path::absolute(game_dir)?);
+                options.executable = exe;
+            }
+
+            if is_edit {
+                manager::set_launch_options(&id, options.clone()).await?;
+            }
+
+            println!("{:#?}", options);
+        }
+        Commands::Unlink { id } => {
+            match manager::unlink_game_dir(&id).await? {
+                true => println!("Restored the game directory"),
+                false => println!("Nothing to remove from the game directory"),
+            }
         }
-        Commands::List => {
-            for collection in manager::list_collections().await? {
+        Commands::List { recent } => {
+            let collections = match recent {
+                true => manager::list_collections_by_recent_use().await?,
+                false => manager::list_collections().await?,
+            };
+
+            for collection in collections {
                 println!("{}", collection.name);
             }
         }
+        Commands::Diagnose { id } => {
+            let suspects = manager::diagnose_last_session(&id).await?;
+
+            if suspects.is_empty() {
+                println!("No problems found in the last session's log");
+            }
+
+            for suspect in suspects {
+                println!("{} (score {})", suspect.plugin, suspect.score);
+
+                for finding in suspect.findings {
+                    println!("  {:?}:\n    {}", finding.kind, finding.excerpt.replace('\n', "\n    "));
+                }
+            }
+        }
+        Commands::Bisect { id, abort: true } => {
+            manager::bisect_abort(&id).await?;
+
+            println!("Bisect stopped, plugins have been restored");
+        }
+        Commands::Bisect { id, abort: false } => {
+            let mut step = match manager::bisect_status(&id).await? {
+                Some(step) => step,
+                None => manager::bisect_start(&id).await?,
+            };
+
+            while let manager::BisectStep::Testing { step: number, remaining, enabled } = &step {
+                println!("Step {}: {} plugins left, testing {}", number, remaining, enabled.join(", "));
+
+                manager::launch_collection_supervised(&id).await?;
+
+                match prompt("Did the problem happen? [y/n/q]")?.as_str() {
+                    "y" | "yes" => step = manager::bisect_answer(&id, true).await?,
+                    "n" | "no" => step = manager::bisect_answer(&id, false).await?,
+                    _ => {
+                        println!("Progress saved, run bisect again to resume");
+
+                        return Ok(());
+                    }
+                }
+            }
+
+            if let manager::BisectStep::Finished { culprits } = step {
+                println!("The problem is caused by {}, plugins have been restored", culprits.join(", "));
+            }
+        }
+        Commands::Logs { id, session: Some(session), player } => {
+            let file = match player {
+                true => manager::SessionLogFile::Player,
+                false => manager::SessionLogFile::BepInEx,
+            };
+
+            print!("{}", manager::read_session_log(&id, &session, file).await?);
+        }
+        Commands::Logs { id, session: None, .. } => {
+            for logs in manager::list_session_logs(&id).await? {
+                println!(
+                    "{} ({} minutes, exit code {})",
+                    logs.id,
+                    logs.session.play_time_secs() / 60,
+                    logs.session.exit_code.map_or("unknown".to_owned(), |x| x.to_string())
+                );
+            }
+        }
         Commands::AddPlugin { id, url } => {
             manager::add_plugin(&id, &url).await?;
         }
+        Commands::Autoremove { id } => {
+            for plugin in manager::autoremove(&id).await? {
+                println!("Removed {}", plugin);
+            }
+        }
+        Commands::Update { id, plugin, version } => {
+            manager::update_plugin(&id, &plugin, version.as_deref()).await?;
+        }
+        Commands::Clone { id, new_name } => {
+            manager::clone_collection(&id, &new_name).await?;
+        }
         Commands::Export { id } => {
             manager::export_collection(&id).await?;
         }
         Commands::Import { path } => {
-            manager::import_collection(path).await?;
+            manager::import_collection(&path).await?;
+        }
+        Commands::Lock { id } => {
+            let path = manager::lock_collection(&id).await?;
+
+            println!("Wrote lockfile to {}", path.display());
+        }
+        Commands::Sync { path } => {
+            let name = manager::sync_from_lockfile(&path).await?;
+
+            println!("Synced collection {}", name);
+        }
+        Commands::Apply { path, dry_run } => {
+            let plan = manager::apply_manifest(&path, dry_run).await?;
+
+            print!("{}", plan);
+        }
+        Commands::ResetConfig { id, plugin } => {
+            let restored = manager::reset_plugin_config(&id, &plugin).await?;
+
+            if restored.is_empty() {
+                println!("{} does not ship any config files", plugin);
+            }
+
+            for file in restored {
+                println!("Restored {}", file.display());
+            }
         }
         Commands::Remove { id } => {
             manager::remove_collection(&id).await?;
         }
-        Commands::Shortcut { id } => {
+        Commands::Rename { id, new_name } => {
+            manager::rename_collection(&id, &new_name).await?;
+        }
+        Commands::Shortcut { id, remove: true } => {
+            if !manager::remove_shortcut(&id).await? {
+                println!("{} has no shortcut", id);
+            }
+        }
+        Commands::Shortcut { id, remove: false } => {
             manager::create_shortcut(&id).await?;
         }
+        Commands::SteamShortcut { id } => {
+            manager::add_steam_shortcut(&id).await?;
+
+            println!("Added {} to Steam, restart Steam to see it", id);
+        }
+        Commands::Snapshot { id, label } => {
+            let snapshot_id = manager::snapshot_collection(&id, label.as_deref()).await?;
+
+            println!("Created snapshot {}", snapshot_id);
+        }
+        Commands::Snapshots { id } => {
+            for snapshot in manager::list_snapshots(&id).await? {
+                println!(
+                    "{}\t{}\t{} plugins\t{}",
+                    snapshot.id,
+                    snapshot.created_at.format("%Y-%m-%d %H:%M:%S"),
+                    snapshot.plugins.len(),
+                    snapshot.label
+                );
+            }
+        }
+        Commands::Restore { id, snapshot } => {
+            manager::restore_snapshot(&id, snapshot).await?;
+        }
     }
 
     Ok(())
 }
+
+/// Prints every manager event to stderr as it is emitted.
+fn print_events() -> Result<()> {
+    let mut events = manager::event::subscribe()?;
+
+    tokio::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(event) => eprintln!("{}", event),
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return,
+            }
+        }
+    });
+
+    Ok(())
+}
+
+fn split_command_line(line: &str) -> Result<Vec<String>> {
+    shlex::split(line).ok_or_else(|| eyre!("failed to parse '{}', check its quotes", line))
+}
+
+fn prompt(message: &str) -> Result<String> {
+    println!("{}", message);
+
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+
+    Ok(answer.trim().to_lowercase())
+}
@@ -1,10 +1,10 @@
 use eyre::OptionExt;
-use manager::FrontendCollection;
+use manager::{ConfigFile, FrontendCollection};
 use crate::logger;
 
 #[tauri::command]
 pub async fn list_collections() -> logger::Result<Vec<FrontendCollection>> {
-    Ok(manager::list_collections().await?.into_iter().map(Into::into).collect())
+    Ok(manager::list_collections_by_recent_use().await?.into_iter().map(Into::into).collect())
 }
 
 #[tauri::command]
@@ -29,3 +29,27 @@ pub async fn shortcut_collection(name: &str) -> logger::Result<()> {
     Ok(
         manager::create_shortcut(name).await?)
 }
+
+#[tauri::command]
+pub async fn steam_shortcut_collection(name: &str) -> logger::Result<()> {
+    Ok(
+        manager::add_steam_shortcut(name).await?)
+}
+
+#[tauri::command]
+pub async fn list_configs(name: &str) -> logger::Result<Vec<ConfigFile>> {
+    Ok(
+        manager::list_configs(name).await?)
+}
+
+#[tauri::command]
+pub async fn set_config_value(
+    name: &str,
+    file: &str,
+    section: &str,
+    key: &str,
+    value: &str,
+) -> logger::Result<()> {
+    Ok(
+        manager::set_config_value(name, file, section, key, value).await?)
+}
@@ -1,17 +1,71 @@
+use tauri::{AppHandle, Emitter};
+use tokio::sync::broadcast::error::RecvError;
+
 mod commands;
 mod logger;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // Desktop and Steam shortcuts start the app with `launch <name>`, which launches
+    // the collection without opening a window, the same as the CLI.
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    if let [command, name] = args.as_slice()
+        && command == "launch"
+    {
+        if let Err(err) = tauri::async_runtime::block_on(launch_from_shortcut(name)) {
+            tracing::error!("failed to launch collection '{}' from shortcut: {:?}", name, err);
+
+            std::process::exit(1);
+        }
+
+        return;
+    }
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .setup(|app| {
+            let handle = app.handle().clone();
+
+            tauri::async_runtime::spawn(async move {
+                if let Err(err) = forward_events(handle).await {
+                    tracing::error!("failed to forward manager events: {:?}", err);
+                }
+            });
+
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             commands::is_first_time,
             commands::collection::list_collections,
             commands::collection::list_collection,
             commands::collection::launch_collection,
-            commands::collection::shortcut_collection
+            commands::collection::shortcut_collection,
+            commands::collection::steam_shortcut_collection,
+            commands::collection::list_configs,
+            commands::collection::set_config_value
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+async fn launch_from_shortcut(name: &str) -> eyre::Result<()> {
+    manager::event::EventState::init().await?;
+
+    manager::launch_collection_detached(name).await
+}
+
+/// Re-emits every manager event to the frontend as `manager-event`.
+async fn forward_events(app: AppHandle) -> eyre::Result<()> {
+    manager::event::EventState::init().await?;
+
+    let mut events = manager::event::subscribe()?;
+
+    loop {
+        match events.recv().await {
+            Ok(event) => app.emit("manager-event", event)?,
+            Err(RecvError::Lagged(skipped)) => tracing::warn!("skipped {} manager events", skipped),
+            Err(RecvError::Closed) => return Ok(()),
+        }
+    }
+}
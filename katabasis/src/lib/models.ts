@@ -10,6 +10,35 @@ export interface ICollection {
 export interface IPlugin {
     enabled: boolean;
     installTime: Date;
+    installReason: "explicit" | "dependency";
     ident: string;
     fullName: string;
 }
+
+// Mirrors the ConfigFile struct on the manager crate.
+export interface IConfigFile {
+    file: string;
+    sections: Array<IConfigSection>;
+}
+
+export interface IConfigSection {
+    name: string;
+    entries: Array<IConfigEntry>;
+}
+
+export interface IConfigEntry {
+    key: string;
+    value:
+        | { type: "boolean"; value: boolean }
+        | { type: "integer"; value: number }
+        | { type: "float"; value: number }
+        | { type: "string"; value: string };
+    description: string | null;
+    settingType: string | null;
+    defaultValue: string | null;
+    acceptableValues:
+        | { type: "list"; values: Array<string> }
+        | { type: "range"; min: number; max: number }
+        | null;
+    multiple: boolean;
+}
@@ -0,0 +1,27 @@
+-- Collections are stored on disk under a stable id rather than their sanitised
+-- name, existing rows are given a random v4 UUID.
+CREATE TABLE collections_new (
+    name TEXT NOT NULL,
+    id TEXT NOT NULL UNIQUE,
+    plugins TEXT NOT NULL,
+    game TEXT NOT NULL,
+    PRIMARY KEY (name)
+);
+
+INSERT INTO collections_new (name, id, plugins, game)
+SELECT
+    name,
+    lower(
+        hex(randomblob(4)) || '-' ||
+        hex(randomblob(2)) || '-' ||
+        '4' || substr(hex(randomblob(2)), 2) || '-' ||
+        substr('89ab', 1 + (abs(random()) % 4), 1) || substr(hex(randomblob(2)), 2) || '-' ||
+        hex(randomblob(6))
+    ),
+    plugins,
+    game
+FROM collections;
+
+DROP TABLE collections;
+
+ALTER TABLE collections_new RENAME TO collections;
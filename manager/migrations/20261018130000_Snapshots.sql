@@ -0,0 +1,10 @@
+CREATE TABLE snapshots (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    collection_id TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    label TEXT NOT NULL,
+    automatic BOOLEAN NOT NULL,
+    plugins TEXT NOT NULL
+);
+
+CREATE INDEX snapshots_collection_id ON snapshots (collection_id);
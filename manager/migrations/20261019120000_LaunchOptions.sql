@@ -0,0 +1,2 @@
+-- Per-collection launch options, stored as JSON like the collection's plugins.
+ALTER TABLE collections ADD COLUMN launch_options TEXT NOT NULL DEFAULT '{}';
@@ -0,0 +1,7 @@
+-- Play time is accumulated separately from the collection row so that saving a
+-- collection loaded before a session ended can't overwrite it.
+CREATE TABLE play_stats (
+    collection_id TEXT NOT NULL PRIMARY KEY,
+    play_time INTEGER NOT NULL DEFAULT 0,
+    last_played TEXT NOT NULL
+);
@@ -0,0 +1,5 @@
+-- At most one bisect can be in progress per collection, its state is stored as JSON.
+CREATE TABLE bisect_sessions (
+    collection_id TEXT NOT NULL PRIMARY KEY,
+    state TEXT NOT NULL
+);
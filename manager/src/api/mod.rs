@@ -1,15 +1,29 @@
 use crate::collection::export::ExportCollection;
-use crate::collection::{install, launch, Collection};
+use crate::collection::bisect::{self, Bisect};
+use crate::collection::lockfile::Lockfile;
+use crate::collection::manifest::{self, Manifest};
+use crate::collection::{config, diagnose, install, launch, session, snapshot, steam_shortcut, Collection};
+use crate::collection::launch::GameRuntime;
+use crate::db::Db;
+use crate::event::{self, Event};
 use crate::state::AppState;
-use crate::targets::{self, Target};
-use crate::utils::paths;
+use crate::targets::{self, Platform, Target};
+use crate::utils::{fs, paths};
 use crate::platforms;
-use eyre::{eyre, Context};
+use eyre::{ensure, eyre, Context};
 use std::path::PathBuf;
 use tokio::process::Command;
+use uuid::Uuid;
 
 pub use {
-  crate::collection::{FrontendCollection, FrontendPlugin}  
+  crate::collection::{FrontendCollection, FrontendPlugin, InstallReason},
+  crate::collection::snapshot::Snapshot,
+  crate::collection::launch::LaunchOptions,
+  crate::collection::session::{GameSession, PlayStats, SessionLogFile, SessionLogs},
+  crate::collection::diagnose::{Finding, FindingKind, Suspect},
+  crate::collection::bisect::BisectStep,
+  crate::collection::manifest::{ManifestPlan, PlanAction},
+  crate::collection::config::{AcceptableValues, ConfigEntry, ConfigFile, ConfigSection, EntryValue},
 };
 
 /// Returns an iterator of all currently supported application
@@ -35,13 +49,74 @@ pub async fn create_collection(name: &str, slug: &str) -> eyre::Result<String> {
     let target = targets::from_slug(slug)
         .ok_or_else(|| eyre!("Slug '{}' does not match any supported games", slug))?;
 
+    ensure!(
+        !state.db().has_collection(name).await?,
+        "a collection named '{}' already exists",
+        name
+    );
+
     let collection = Collection {
         name: name.to_owned(),
+        id: Uuid::new_v4(),
         game: target,
         plugins: vec![],
+        launch_options: Default::default(),
+    };
+
+    let collection_dir = paths::collection_dir(&collection.id);
+
+    ensure!(
+        !collection_dir.exists(),
+        "collection directory '{}' already exists",
+        collection_dir.display()
+    );
+
+    tokio::fs::create_dir_all(&collection_dir).await?;
+
+    state.db().save_collection(&collection).await?;
+
+    Ok(collection.name)
+}
+
+/// Creates a copy of an existing [`Collection`] under a new name. Plugin files
+/// are hard linked to save space while mutable files, such as configs, are
+/// copied so both collections can change independently.
+pub async fn clone_collection(src_name: &str, new_name: &str) -> eyre::Result<String> {
+    let state = AppState::get().await?;
+    let src = state.db().load_collection(src_name).await?;
+
+    ensure!(
+        !state.db().has_collection(new_name).await?,
+        "a collection named '{}' already exists",
+        new_name
+    );
+
+    let collection = Collection {
+        name: new_name.to_owned(),
+        id: Uuid::new_v4(),
+        game: src.game,
+        plugins: src.plugins.clone(),
+        launch_options: src.launch_options.clone(),
     };
 
-    tokio::fs::create_dir_all(paths::collection_dir(&collection.name)).await?;
+    let src_dir = paths::collection_dir(&src.id);
+    let collection_dir = paths::collection_dir(&collection.id);
+
+    tokio::fs::create_dir_all(&collection_dir).await?;
+
+    let copy_result = fs::copy_dir_contents_to(&src_dir, collection_dir.clone(), |rel_path, _| {
+        Ok(fs::CopyFileOpts {
+            should_copy_file: collection.game.mod_loader.is_mutable_file(rel_path),
+            should_overwrite_file: true,
+        })
+    })
+    .await;
+
+    if let Err(err) = copy_result {
+        tokio::fs::remove_dir_all(&collection_dir).await?;
+
+        return Err(err.wrap_err(format!("failed to clone collection '{}'", src_name)));
+    }
 
     state.db().save_collection(&collection).await?;
 
@@ -58,29 +133,400 @@ pub async fn launch_collection_detached(name: &str) -> eyre::Result<()> {
         .await
         .with_context(|| format!("failed to load collection with name '{}'", name))?;
 
-    let platform = collection.game.platforms.iter().next().unwrap();
-    let game_dir = platforms::game_dir(collection.game, platform)?;
-    let collection_dir = PathBuf::from(paths::collection_dir(&collection.name));
+    let (mut command, _) = prepare_launch(&collection).await?;
 
-    launch::link_files(&collection_dir, &game_dir).await?;
+    command.spawn()?;
 
-    let mut command = if let Some(x) = platforms::launch_command(collection.game, platform) {
-        x
-    } else {
-        launch::app_path(&game_dir).await.map(Command::new)?
+    Ok(())
+}
+
+/// Launches the [`Collection`] and waits for the game to exit, emitting events
+/// when it starts and exits. The session's play time is recorded against the
+/// collection.
+pub async fn launch_collection_supervised(name: &str) -> eyre::Result<GameSession> {
+    let state = AppState::get().await?;
+
+    let collection = state
+        .db()
+        .load_collection(name)
+        .await
+        .with_context(|| format!("failed to load collection with name '{}'", name))?;
+
+    run_supervised(state.db(), &collection).await
+}
+
+/// Launches the [`Collection`] with only the mod loader enabled, then restores
+/// every plugin's enabled state once the game exits. A snapshot is taken first in
+/// case the states can't be restored.
+pub async fn launch_collection_safe(name: &str) -> eyre::Result<GameSession> {
+    let state = AppState::get().await?;
+
+    let mut collection = state
+        .db()
+        .load_collection(name)
+        .await
+        .with_context(|| format!("failed to load collection with name '{}'", name))?;
+
+    snapshot::take_automatic(state.db(), &collection, "Before safe launch").await?;
+
+    let original_states = collection.plugins.iter().map(|x| x.enabled).collect::<Vec<_>>();
+    let safe_states = bisect::enabled_states(&collection.plugins, &[], &collection.game.mod_loader);
+
+    let session = async {
+        for (index, enabled) in safe_states.into_iter().enumerate() {
+            install::switch_plugin(&mut collection, index, enabled).await?;
+        }
+
+        state.db().save_collection(&collection).await?;
+
+        run_supervised(state.db(), &collection).await
+    }.await;
+
+    // Every plugin is restored even if switching or the launch failed part way, a
+    // failed restore doesn't stop the remaining plugins from being restored.
+    let mut restored = Ok(());
+
+    for (index, enabled) in original_states.into_iter().enumerate() {
+        if let Err(error) = install::switch_plugin(&mut collection, index, enabled).await {
+            tracing::error!(
+                "failed to restore {} after safe launch: {:?}",
+                collection.plugins[index].ident().as_str(),
+                error
+            );
+
+            if restored.is_ok() {
+                restored = Err(error);
+            }
+        }
+    }
+
+    let saved = state.db().save_collection(&collection).await;
+
+    let session = session?;
+
+    restored?;
+    saved?;
+
+    Ok(session)
+}
+
+async fn run_supervised(db: &Db, collection: &Collection) -> eyre::Result<GameSession> {
+    let (mut command, launch) = prepare_launch(collection).await?;
+
+    let session = session::supervise(
+        collection,
+        &launch.game_dir,
+        command.spawn()?,
+        launch.through_launcher,
+    ).await?;
+
+    db.record_play_session(&collection.id, session.started_at, session.play_time_secs()).await?;
+
+    session::save_logs(collection, &session, &launch.game_dir).await?;
+
+    Ok(session)
+}
+
+/// Lists the logs saved from the [`Collection`]'s supervised launches, newest first.
+pub async fn list_session_logs(collection_name: &str) -> eyre::Result<Vec<SessionLogs>> {
+    let state = AppState::get().await?;
+    let collection = state.db().load_collection(collection_name).await?;
+
+    session::list_logs(&collection).await
+}
+
+/// Analyses the BepInEx log of the [`Collection`]'s most recent supervised launch,
+/// returning the plugins suspected of causing problems, most likely first.
+///
+/// Log lines are only attributed to a plugin by its package name or the names of
+/// the assemblies in its folder. Lines logged under a plugin's BepInEx GUID or
+/// display name that differs from those are not attributed to any plugin.
+pub async fn diagnose_last_session(collection_name: &str) -> eyre::Result<Vec<Suspect>> {
+    let state = AppState::get().await?;
+    let collection = state.db().load_collection(collection_name).await?;
+
+    let last_session = session::list_logs(&collection)
+        .await?
+        .into_iter()
+        .find(|x| x.files.contains(&SessionLogFile::BepInEx))
+        .ok_or_else(|| eyre!("collection '{}' has no saved BepInEx logs to diagnose", collection_name))?;
+
+    let log = session::read_log(&collection, &last_session.id, SessionLogFile::BepInEx).await?;
+
+    Ok(diagnose::analyse(&log, &diagnose::plugin_names(&collection).await?))
+}
+
+/// Starts bisecting the [`Collection`]'s plugins to find the one causing a problem,
+/// enabling the first half of them. Fails if a bisect is already in progress.
+pub async fn bisect_start(collection_name: &str) -> eyre::Result<BisectStep> {
+    let state = AppState::get().await?;
+    let mut collection = state.db().load_collection(collection_name).await?;
+
+    ensure!(
+        state.db().load_bisect(&collection.id).await?.is_none(),
+        "collection '{}' is already being bisected",
+        collection_name
+    );
+
+    let bisect = Bisect::new(&collection)?;
+
+    snapshot::take_automatic(state.db(), &collection, "Before bisect").await?;
+
+    bisect.apply(&mut collection).await?;
+
+    state.db().save_collection(&collection).await?;
+    state.db().save_bisect(&bisect).await?;
+
+    Ok(bisect.current_step())
+}
+
+/// Returns the current step of the [`Collection`]'s bisect, if one is in progress.
+pub async fn bisect_status(collection_name: &str) -> eyre::Result<Option<BisectStep>> {
+    let state = AppState::get().await?;
+    let collection = state.db().load_collection(collection_name).await?;
+
+    Ok(state.db().load_bisect(&collection.id).await?.map(|x| x.current_step()))
+}
+
+/// Records whether the problem happened with the current step's plugins and moves
+/// on to the next step. Once finished the original enabled states are restored.
+pub async fn bisect_answer(collection_name: &str, problem_happened: bool) -> eyre::Result<BisectStep> {
+    let state = AppState::get().await?;
+    let mut collection = state.db().load_collection(collection_name).await?;
+
+    let mut bisect = state.db()
+        .load_bisect(&collection.id)
+        .await?
+        .ok_or_else(|| eyre!("collection '{}' is not being bisected", collection_name))?;
+
+    bisect.answer(problem_happened);
+    bisect.apply(&mut collection).await?;
+
+    state.db().save_collection(&collection).await?;
+
+    match bisect.is_finished() {
+        true => state.db().remove_bisect(&collection.id).await?,
+        false => state.db().save_bisect(&bisect).await?,
+    }
+
+    Ok(bisect.current_step())
+}
+
+/// Stops the [`Collection`]'s bisect, restoring the original enabled states.
+pub async fn bisect_abort(collection_name: &str) -> eyre::Result<()> {
+    let state = AppState::get().await?;
+    let mut collection = state.db().load_collection(collection_name).await?;
+
+    let Some(mut bisect) = state.db().load_bisect(&collection.id).await? else {
+        return Ok(());
     };
 
-    launch::add_loader_args(&mut command, &collection_dir, &collection.game.mod_loader).await?;
+    bisect.abort();
+    bisect.apply(&mut collection).await?;
 
-    command.spawn()?;
+    state.db().save_collection(&collection).await?;
+    state.db().remove_bisect(&collection.id).await
+}
+
+pub async fn read_session_log(
+    collection_name: &str,
+    session_id: &str,
+    file: SessionLogFile,
+) -> eyre::Result<String> {
+    let state = AppState::get().await?;
+    let collection = state.db().load_collection(collection_name).await?;
+
+    session::read_log(&collection, session_id, file).await
+}
+
+struct PreparedLaunch {
+    game_dir: PathBuf,
+    /// Whether the command starts a launcher, such as Steam, rather than the game.
+    through_launcher: bool,
+}
+
+/// Works out where the [`Collection`]'s game is installed and the platform that
+/// launches it, which is `None` when the collection overrides the game directory.
+fn game_install(collection: &Collection) -> eyre::Result<(PathBuf, Option<Platform>)> {
+    if let Some(game_dir) = &collection.launch_options.game_dir {
+        return Ok((game_dir.clone(), None));
+    }
+
+    let mut errors = Vec::new();
+
+    for platform in collection.game.platforms.iter() {
+        match platforms::game_dir(collection.game, platform) {
+            Ok(game_dir) => return Ok((game_dir, Some(platform))),
+            Err(err) => errors.push(format!("{:?}: {}", platform, err)),
+        }
+    }
+
+    ensure!(
+        !errors.is_empty(),
+        "{} isn't available on any supported platform, set its game directory",
+        collection.game.name
+    );
+
+    Err(eyre!(
+        "failed to find {} on any platform, set its game directory\n{}",
+        collection.game.name,
+        errors.join("\n")
+    ))
+}
+
+/// Links the required files into the game directory and builds the command used
+/// to launch the [`Collection`].
+async fn prepare_launch(collection: &Collection) -> eyre::Result<(Command, PreparedLaunch)> {
+    let (game_dir, platform) = game_install(collection)?;
+
+    launch::link_files(collection, &game_dir).await?;
+
+    let runtime = GameRuntime::detect(&game_dir).await?;
+
+    // Steam doesn't pass our environment on to the game, which native Linux builds
+    // need to preload Doorstop, so those are started directly.
+    let launch_directly = cfg!(unix) && runtime == GameRuntime::Native && collection.game.linux.is_some();
+
+    let launcher_command = platform
+        .and_then(|platform| platforms::launch_command(collection.game, platform))
+        .filter(|_| !launch_directly);
+
+    let (mut command, through_launcher) = match launcher_command {
+        Some(command) => (command, true),
+        None => {
+            // A wrapper such as `wine` or `umu-run` can run the game without a launcher.
+            ensure!(
+                runtime == GameRuntime::Native || !collection.launch_options.wrapper.is_empty(),
+                "{} has to be launched through a launcher or wrapper to run under Proton",
+                collection.game.name
+            );
+
+            (launch::app_path(collection, &game_dir, runtime).await.map(Command::new)?, false)
+        }
+    };
+
+    launch::add_loader_args(&mut command, collection, &game_dir, runtime).await?;
+
+    if runtime == GameRuntime::Proton
+        && let Some(platform) = platform
+        && through_launcher
+        && let Some(launch_option) = platforms::missing_proton_launch_option(collection.game, platform)
+    {
+        event::emit(Event::LaunchOptionRequired {
+            collection: collection.name.clone(),
+            launch_option,
+        })?;
+    }
+
+    let command = apply_launch_options(collection, command, through_launcher)?;
+
+    Ok((command, PreparedLaunch { game_dir, through_launcher }))
+}
+
+/// Adds the [`Collection`]'s launch options to the command. Through a launcher the
+/// environment and wrapper have to be set in the launcher, which the user is told
+/// about.
+fn apply_launch_options(collection: &Collection, command: Command, through_launcher: bool) -> eyre::Result<Command> {
+    if through_launcher
+        && let Some(launch_option) = launch::launcher_launch_option(&collection.launch_options)
+    {
+        event::emit(Event::LaunchOptionRequired {
+            collection: collection.name.clone(),
+            launch_option,
+        })?;
+    }
+
+    let command = launch::apply_launch_options(command, &collection.launch_options, through_launcher);
+
+    Ok(platforms::with_url_args(command))
+}
+
+/// Launches the [`Collection`]'s game without any mods, after removing the files
+/// previous launches placed in the game directory. The collection's launch
+/// options are still applied.
+pub async fn launch_vanilla(name: &str) -> eyre::Result<()> {
+    let state = AppState::get().await?;
+    let collection = state.db().load_collection(name).await?;
+
+    launch::unlink_game_dir(collection.game).await?;
+
+    let (game_dir, platform) = game_install(&collection)?;
+
+    let (command, through_launcher) = match platform.and_then(|platform| platforms::launch_command(collection.game, platform)) {
+        Some(command) => (command, true),
+        None => {
+            let runtime = GameRuntime::detect(&game_dir).await?;
+
+            (launch::app_path(&collection, &game_dir, runtime).await.map(Command::new)?, false)
+        }
+    };
+
+    apply_launch_options(&collection, command, through_launcher)?.spawn()?;
 
     Ok(())
 }
 
+/// Removes the files launching the [`Collection`] placed in its game directory and
+/// restores any game files they replaced, so launching the game outside of the
+/// manager is unmodded. Returns whether there was anything to remove.
+pub async fn unlink_game_dir(name: &str) -> eyre::Result<bool> {
+    let state = AppState::get().await?;
+    let collection = state.db().load_collection(name).await?;
+
+    launch::unlink_game_dir(collection.game).await
+}
+
+/// Returns the total play time and last played time of the [`Collection`].
+pub async fn play_stats(collection_name: &str) -> eyre::Result<PlayStats> {
+    let state = AppState::get().await?;
+    let collection = state.db().load_collection(collection_name).await?;
+
+    Ok(state.db().load_play_stats().await?.remove(&collection.id).unwrap_or_default())
+}
+
+/// Returns the extra arguments, environment variables and wrapper command used
+/// when launching the [`Collection`].
+pub async fn launch_options(collection_name: &str) -> eyre::Result<LaunchOptions> {
+    let state = AppState::get().await?;
+
+    Ok(state.db().load_collection(collection_name).await?.launch_options)
+}
+
+/// Saves the [`Collection`]'s launch options, after checking that any game
+/// directory or executable override exists.
+pub async fn set_launch_options(collection_name: &str, options: LaunchOptions) -> eyre::Result<()> {
+    let state = AppState::get().await?;
+    let mut collection = state.db().load_collection(collection_name).await?;
+
+    options.validate()?;
+
+    collection.launch_options = options;
+
+    state.db().save_collection(&collection).await
+}
+
 pub async fn list_collections() -> eyre::Result<Vec<Collection>> {
     let state = AppState::get().await?;
 
-    Ok(state.db().load_all_collections().await?)
+    state.db().load_all_collections().await
+}
+
+/// Lists every [`Collection`], most recently played first. Collections that have
+/// never been played come last, ordered by name.
+pub async fn list_collections_by_recent_use() -> eyre::Result<Vec<Collection>> {
+    let state = AppState::get().await?;
+
+    let play_stats = state.db().load_play_stats().await?;
+    let mut collections = state.db().load_all_collections().await?;
+
+    collections.sort_by(|a, b| {
+        let a_played = play_stats.get(&a.id).and_then(|x| x.last_played);
+        let b_played = play_stats.get(&b.id).and_then(|x| x.last_played);
+
+        b_played.cmp(&a_played).then_with(|| a.name.cmp(&b.name))
+    });
+
+    Ok(collections)
 }
 
 pub async fn add_plugin(collection_name: &str, url: &str) -> eyre::Result<()> {
@@ -88,6 +534,8 @@ pub async fn add_plugin(collection_name: &str, url: &str) -> eyre::Result<()> {
 
     let mut collection = state.db().load_collection(collection_name).await?;
 
+    snapshot::take_automatic(state.db(), &collection, &format!("Before installing {}", url)).await?;
+
     install::install_with_deps(
         &mut collection,
         url
@@ -98,6 +546,57 @@ pub async fn add_plugin(collection_name: &str, url: &str) -> eyre::Result<()> {
     Ok(())
 }
 
+/// Replaces a plugin in the [`Collection`] with another version of its package,
+/// the latest unless a version is provided, merging the new default configs into
+/// the user's. The plugin can be given by its full ident or package name.
+pub async fn update_plugin(collection_name: &str, ident: &str, version: Option<&str>) -> eyre::Result<()> {
+    let state = AppState::get().await?;
+    let mut collection = state.db().load_collection(collection_name).await?;
+
+    let index = collection.plugins
+        .iter()
+        .position(|x| x.ident().as_str() == ident || x.ident().full_name() == ident)
+        .ok_or_else(|| eyre!("collection '{}' has no plugin '{}'", collection_name, ident))?;
+
+    snapshot::take_automatic(state.db(), &collection, &format!("Before updating {}", ident)).await?;
+
+    install::update_plugin(&mut collection, index, version).await?;
+
+    state.db().save_collection(&collection).await?;
+
+    Ok(())
+}
+
+/// Uninstalls every plugin in the [`Collection`] that was only installed as a
+/// dependency and is no longer required by any other plugin. Returns the
+/// identifiers of the removed plugins.
+pub async fn autoremove(collection_name: &str) -> eyre::Result<Vec<String>> {
+    let state = AppState::get().await?;
+    let mut collection = state.db().load_collection(collection_name).await?;
+
+    let mut removed = Vec::new();
+    let orphans = install::orphaned_plugins(&collection.plugins, &collection.game.mod_loader);
+
+    if orphans.is_empty() {
+        return Ok(removed);
+    }
+
+    snapshot::take_automatic(state.db(), &collection, "Before autoremove").await?;
+
+    // Removing from the back keeps the remaining orphan indices valid.
+    for index in orphans.into_iter().rev() {
+        let plugin = install::uninstall_plugin(&mut collection, index).await?;
+
+        tracing::info!("autoremoved orphaned plugin {}", plugin.ident().as_str());
+
+        removed.push(plugin.ident().as_str().to_owned());
+    }
+
+    state.db().save_collection(&collection).await?;
+
+    Ok(removed)
+}
+
 pub async fn clear_cache() -> eyre::Result<()> {
     let cache_dir = paths::cache_dir();
 
@@ -130,16 +629,210 @@ pub async fn import_collection(collection_path: &str) -> eyre::Result<()> {
     Ok(())
 }
 
+/// Writes a lockfile for the [`Collection`] pinning every plugin's version, download
+/// URL and archive hash. Returns the path of the lockfile.
+pub async fn lock_collection(collection_name: &str) -> eyre::Result<PathBuf> {
+    let state = AppState::get().await?;
+    let collection = state.db().load_collection(collection_name).await?;
+    let lockfile = Lockfile::from_collection(&collection).await?;
+
+    let lockfile_path = paths::collection_lockfile_path(&collection.name)?;
+    lockfile.write(&lockfile_path).await?;
+
+    Ok(lockfile_path)
+}
+
+/// Rebuilds the collection described by a lockfile, creating it if it does not
+/// exist. Fails without changing the collection if any archive hash differs from
+/// the lockfile. Returns the name of the collection.
+pub async fn sync_from_lockfile(lockfile_path: &str) -> eyre::Result<String> {
+    let state = AppState::get().await?;
+    let lockfile = Lockfile::from_file(lockfile_path).await?;
+    let name = &lockfile.collection.name;
+
+    let mut collection = if state.db().has_collection(name).await? {
+        let collection = state.db().load_collection(name).await?;
+
+        snapshot::take_automatic(state.db(), &collection, "Before syncing from lockfile").await?;
+
+        collection
+    } else {
+        let new_name = create_collection(name, &lockfile.collection.slug).await?;
+
+        state.db().load_collection(&new_name).await?
+    };
+
+    lockfile.sync(&mut collection).await?;
+
+    state.db().save_collection(&collection).await?;
+
+    Ok(collection.name)
+}
+
+/// Brings a [`Collection`] in line with a declarative manifest file, creating it
+/// if it doesn't exist. When `dry_run` is set the plan is returned without making
+/// any changes.
+pub async fn apply_manifest(manifest_path: &str, dry_run: bool) -> eyre::Result<ManifestPlan> {
+    let state = AppState::get().await?;
+    let manifest = Manifest::from_file(manifest_path).await?;
+
+    let existing = match state.db().has_collection(&manifest.name).await? {
+        true => Some(state.db().load_collection(&manifest.name).await?),
+        false => None,
+    };
+
+    let plan = manifest.plan(existing.as_ref()).await?;
+
+    if dry_run || plan.is_empty() {
+        return Ok(plan);
+    }
+
+    let mut collection = match existing {
+        Some(collection) => {
+            snapshot::take_automatic(state.db(), &collection, "Before applying manifest").await?;
+
+            collection
+        }
+        None => {
+            let new_name = create_collection(&manifest.name, &manifest.target).await?;
+
+            state.db().load_collection(&new_name).await?
+        }
+    };
+
+    manifest::apply(&plan, &manifest, &mut collection).await?;
+
+    state.db().save_collection(&collection).await?;
+
+    Ok(plan)
+}
+
+/// Parses every BepInEx style `.cfg` file in the [`Collection`]'s config directory.
+pub async fn list_configs(collection_name: &str) -> eyre::Result<Vec<ConfigFile>> {
+    let state = AppState::get().await?;
+    let collection = state.db().load_collection(collection_name).await?;
+
+    config::list_files(&collection).await
+}
+
+/// Sets a single entry of a config file, validating the value against the entry's
+/// type and acceptable values. Comments in the file are kept.
+pub async fn set_config_value(
+    collection_name: &str,
+    file: &str,
+    section: &str,
+    key: &str,
+    value: &str,
+) -> eyre::Result<()> {
+    let state = AppState::get().await?;
+    let collection = state.db().load_collection(collection_name).await?;
+
+    config::set_entry(&collection, file, section, key, value).await
+}
+
+/// Restores the config files that ship with a plugin over the [`Collection`]'s
+/// copies, backing up any overwritten files first. The plugin can be given by its
+/// full ident or package name. Returns the restored files.
+pub async fn reset_plugin_config(collection_name: &str, ident: &str) -> eyre::Result<Vec<PathBuf>> {
+    let state = AppState::get().await?;
+    let collection = state.db().load_collection(collection_name).await?;
+
+    let plugin = collection.plugins
+        .iter()
+        .find(|x| x.ident().as_str() == ident || x.ident().full_name() == ident)
+        .ok_or_else(|| eyre!("collection '{}' has no plugin '{}'", collection_name, ident))?;
+
+    let plugin_cache = install::downloader::ensure_cached(&collection, plugin.ident()).await?;
+
+    config::restore_defaults(&collection, &plugin_cache).await
+}
+
 pub async fn remove_collection(collection_name: &str) -> eyre::Result<()> {
     let state = AppState::get().await?;
     let collection = state.db().load_collection(collection_name).await?;
 
-    tokio::fs::remove_dir_all(paths::collection_dir(&collection.name)).await?;
+    // Removed first as it fails while Steam is running, before anything is deleted.
+    steam_shortcut::remove(&collection).await?;
+
+    tokio::fs::remove_dir_all(paths::collection_dir(&collection.id)).await?;
+    launch::remove_link(&collection).await?;
+    snapshot::remove_all(state.db(), &collection).await?;
+    state.db().remove_play_stats(&collection.id).await?;
+    session::remove_all_logs(&collection).await?;
+    state.db().remove_bisect(&collection.id).await?;
     state.db().remove_collection(&collection).await?;
 
     Ok(())
 }
 
+/// Records the [`Collection`]'s plugins, their enabled states and a copy of its
+/// config directory. Returns the id of the new snapshot.
+pub async fn snapshot_collection(collection_name: &str, label: Option<&str>) -> eyre::Result<i64> {
+    let state = AppState::get().await?;
+    let collection = state.db().load_collection(collection_name).await?;
+
+    snapshot::take(state.db(), &collection, label.unwrap_or("Manual snapshot"), false).await
+}
+
+/// Lists every snapshot of the [`Collection`], newest first.
+pub async fn list_snapshots(collection_name: &str) -> eyre::Result<Vec<Snapshot>> {
+    let state = AppState::get().await?;
+    let collection = state.db().load_collection(collection_name).await?;
+
+    state.db().load_snapshots(&collection.id).await
+}
+
+/// Rolls the [`Collection`] back to a snapshot, reinstalling the recorded plugin
+/// versions and restoring the config directory. The current state is snapshotted
+/// first so the restore can be undone.
+pub async fn restore_snapshot(collection_name: &str, snapshot_id: i64) -> eyre::Result<()> {
+    let state = AppState::get().await?;
+    let mut collection = state.db().load_collection(collection_name).await?;
+    let snapshot = state.db().load_snapshot(snapshot_id).await?;
+
+    snapshot::take_automatic(
+        state.db(),
+        &collection,
+        &format!("Before restoring snapshot {}", snapshot_id),
+    ).await?;
+
+    snapshot::restore(&mut collection, &snapshot).await?;
+
+    state.db().save_collection(&collection).await?;
+
+    Ok(())
+}
+
+/// Renames a [`Collection`] and recreates its desktop and Steam shortcuts if it
+/// had them. The collection's directory is based on its id so it does not move.
+pub async fn rename_collection(old_name: &str, new_name: &str) -> eyre::Result<()> {
+    let state = AppState::get().await?;
+    let mut collection = state.db().load_collection(old_name).await?;
+
+    if old_name == new_name {
+        return Ok(());
+    }
+
+    // Checked before renaming, the Steam shortcut can't be updated while it runs.
+    if steam_shortcut::has_shortcut(&collection).await? {
+        steam_shortcut::ensure_steam_closed()?;
+    }
+
+    state.db().rename_collection(old_name, new_name).await?;
+
+    let had_shortcut = launch::remove_link(&collection).await?;
+
+    collection.name = new_name.to_owned();
+
+    if had_shortcut {
+        launch::create_link(&collection).await?;
+    }
+
+    steam_shortcut::rename(&collection).await?;
+
+    Ok(())
+}
+
 pub fn log_path() -> PathBuf {
     paths::log_path()
 }
@@ -152,3 +845,23 @@ pub async fn create_shortcut(collection_name: &str) -> eyre::Result<()> {
 
     Ok(())
 }
+
+/// Adds the [`Collection`] to the current user's Steam library as a non-Steam game,
+/// with the game's artwork. Adding it again updates the existing shortcut. Steam
+/// has to be restarted for the shortcut to show up.
+pub async fn add_steam_shortcut(collection_name: &str) -> eyre::Result<()> {
+    let state = AppState::get().await?;
+    let collection = state.db().load_collection(collection_name).await?;
+
+    steam_shortcut::add(&collection).await?;
+
+    Ok(())
+}
+
+/// Removes the [`Collection`]'s desktop shortcuts, returns whether there were any.
+pub async fn remove_shortcut(collection_name: &str) -> eyre::Result<bool> {
+    let state = AppState::get().await?;
+    let collection = state.db().load_collection(collection_name).await?;
+
+    launch::remove_link(&collection).await
+}
@@ -0,0 +1,238 @@
+//! Guided search for the plugin causing a problem, by repeatedly disabling half
+//! of the remaining suspects.
+
+use std::collections::HashSet;
+use eyre::{ensure, Result};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::collection::{install, Collection, InstallReason, Plugin};
+use crate::targets::ModLoader;
+
+/// A bisect in progress, stored so it can be resumed between launches.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bisect {
+    pub collection_id: Uuid,
+    /// Full names of the explicitly installed plugins that may be at fault.
+    pub suspects: Vec<String>,
+    /// The suspects enabled for the current step.
+    pub testing: Vec<String>,
+    pub step: u32,
+    /// Full names of every plugin that was enabled before the bisect started.
+    pub originally_enabled: Vec<String>,
+}
+
+/// What the user should do next.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum BisectStep {
+    /// Launch the game with the listed suspects enabled and check for the problem.
+    Testing {
+        step: u32,
+        remaining: usize,
+        enabled: Vec<String>,
+    },
+    /// The bisect is over, these plugins are left as the cause.
+    Finished {
+        culprits: Vec<String>,
+    },
+}
+
+impl Bisect {
+    /// Starts a bisect over every enabled, explicitly installed plugin.
+    pub fn new(collection: &Collection) -> Result<Self> {
+        let mod_loader = &collection.game.mod_loader;
+
+        let suspects = collection.plugins
+            .iter()
+            .filter(|x| {
+                x.enabled
+                    && x.reason == InstallReason::Explicit
+                    && !mod_loader.is_loader_plugin(x.ident().full_name())
+            })
+            .map(|x| x.ident().full_name().to_owned())
+            .collect::<Vec<_>>();
+
+        ensure!(
+            suspects.len() > 1,
+            "collection '{}' needs at least two enabled plugins to bisect",
+            collection.name
+        );
+
+        let originally_enabled = collection.plugins
+            .iter()
+            .filter(|x| x.enabled)
+            .map(|x| x.ident().full_name().to_owned())
+            .collect();
+
+        let mut bisect = Self {
+            collection_id: collection.id,
+            suspects,
+            testing: Vec::new(),
+            step: 0,
+            originally_enabled,
+        };
+
+        bisect.next_step();
+
+        Ok(bisect)
+    }
+
+    /// Narrows down the suspects based on whether the problem happened with the
+    /// current step's plugins enabled.
+    pub fn answer(&mut self, problem_happened: bool) {
+        if problem_happened {
+            self.suspects = std::mem::take(&mut self.testing);
+        }
+        else {
+            self.suspects.retain(|x| !self.testing.contains(x));
+        }
+
+        self.next_step();
+    }
+
+    /// Ends the bisect early without any culprits, applying it afterwards restores
+    /// the original enabled states.
+    pub fn abort(&mut self) {
+        self.suspects.clear();
+        self.testing.clear();
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.suspects.len() <= 1
+    }
+
+    pub fn current_step(&self) -> BisectStep {
+        match self.is_finished() {
+            true => BisectStep::Finished { culprits: self.suspects.clone() },
+            false => BisectStep::Testing {
+                step: self.step,
+                remaining: self.suspects.len(),
+                enabled: self.testing.clone(),
+            },
+        }
+    }
+
+    fn next_step(&mut self) {
+        self.testing = match self.is_finished() {
+            true => Vec::new(),
+            false => self.suspects[..self.suspects.len() / 2].to_vec(),
+        };
+
+        self.step += 1;
+    }
+
+    /// Switches the [`Collection`]'s plugins to match the current step. When the
+    /// bisect is finished the original enabled states are restored.
+    pub async fn apply(&self, collection: &mut Collection) -> Result<()> {
+        let states = match self.is_finished() {
+            true => collection.plugins
+                .iter()
+                .map(|x| self.originally_enabled.iter().any(|name| name == x.ident().full_name()))
+                .collect(),
+            false => enabled_states(&collection.plugins, &self.testing, &collection.game.mod_loader),
+        };
+
+        for (index, enabled) in states.into_iter().enumerate() {
+            install::switch_plugin(collection, index, enabled).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Works out which plugins should be enabled so that only the listed plugins, the
+/// mod loader and their dependencies are loaded.
+pub fn enabled_states(plugins: &[Plugin], enabled: &[String], mod_loader: &ModLoader) -> Vec<bool> {
+    let mut states = plugins
+        .iter()
+        .map(|x| {
+            let name = x.ident().full_name();
+
+            mod_loader.is_loader_plugin(name) || enabled.iter().any(|x| x == name)
+        })
+        .collect::<Vec<_>>();
+
+    // Enabling a dependency can require its own dependencies, so keep going until nothing changes.
+    loop {
+        let required = plugins
+            .iter()
+            .zip(&states)
+            .filter(|(_, enabled)| **enabled)
+            .flat_map(|(plugin, _)| plugin.dependencies.iter().map(|x| x.as_str()))
+            .collect::<HashSet<_>>();
+
+        let mut changed = false;
+
+        for (plugin, state) in plugins.iter().zip(states.iter_mut()) {
+            if !*state && required.contains(plugin.ident().full_name()) {
+                *state = true;
+                changed = true;
+            }
+        }
+
+        if !changed {
+            return states;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::targets;
+
+    fn plugin(ident: &str, reason: InstallReason, dependencies: &[&str]) -> Plugin {
+        Plugin::from_moved_ident(ident.parse().unwrap())
+            .with_reason(reason)
+            .with_dependencies(dependencies.iter().map(|x| x.parse().unwrap()).collect())
+    }
+
+    fn collection() -> Collection {
+        Collection {
+            name: "EXAMPLE".to_owned(),
+            id: Uuid::new_v4(),
+            game: targets::from_slug("valheim").unwrap(),
+            launch_options: Default::default(),
+            plugins: vec![
+                plugin("denikson-BepInExPack_Valheim-5.4.2202", InstallReason::Dependency, &[]),
+                plugin("Example-A-1.0.0", InstallReason::Explicit, &["Example-Shared"]),
+                plugin("Example-B-1.0.0", InstallReason::Explicit, &[]),
+                plugin("Example-C-1.0.0", InstallReason::Explicit, &["Example-Shared"]),
+                plugin("Example-D-1.0.0", InstallReason::Explicit, &[]),
+                plugin("Example-Shared-1.0.0", InstallReason::Dependency, &["Example-Leaf"]),
+                plugin("Example-Leaf-1.0.0", InstallReason::Dependency, &[]),
+            ],
+        }
+    }
+
+    #[test]
+    fn dependencies_stay_consistent() {
+        let collection = collection();
+
+        let states = enabled_states(
+            &collection.plugins,
+            &["Example-A".to_owned()],
+            &collection.game.mod_loader,
+        );
+
+        assert_eq!(states, [true, true, false, false, false, true, true]);
+    }
+
+    #[test]
+    fn bisect_finds_culprit() {
+        let mut bisect = Bisect::new(&collection()).unwrap();
+
+        assert_eq!(bisect.testing, ["Example-A", "Example-B"]);
+
+        // Example-C is at fault, so the problem doesn't happen with A and B.
+        bisect.answer(false);
+
+        assert_eq!(bisect.testing, ["Example-C"]);
+
+        bisect.answer(true);
+
+        assert_eq!(bisect.current_step(), BisectStep::Finished { culprits: vec!["Example-C".to_owned()] });
+        assert_eq!(bisect.step, 3);
+    }
+}
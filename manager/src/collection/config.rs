@@ -0,0 +1,720 @@
+//! Parsing BepInEx style `.cfg` files and writing single values back while
+//! keeping the rest of the file, including comments, untouched.
+
+use std::path::{Component, Path, PathBuf};
+use eyre::{bail, ensure, eyre, Result};
+use serde::Serialize;
+use crate::collection::Collection;
+use crate::utils::{fs, paths};
+
+/// A `.cfg` file within a collection's config directory.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigFile {
+    /// Path relative to the config directory, always using `/` separators.
+    pub file: String,
+    pub sections: Vec<ConfigSection>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigSection {
+    pub name: String,
+    pub entries: Vec<ConfigEntry>,
+}
+
+/// A single `key = value` entry, along with the information BepInEx writes in
+/// the comments above it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigEntry {
+    pub key: String,
+    pub value: EntryValue,
+    pub description: Option<String>,
+    /// The .NET type name of the setting, e.g. `Boolean`, `Single` or an enum name.
+    pub setting_type: Option<String>,
+    pub default_value: Option<String>,
+    pub acceptable_values: Option<AcceptableValues>,
+    /// Set for flag enums, which accept several comma separated values.
+    pub multiple: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", content = "value", rename_all = "camelCase")]
+pub enum EntryValue {
+    Boolean(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum AcceptableValues {
+    List { values: Vec<String> },
+    Range { min: f64, max: f64 },
+}
+
+impl ConfigEntry {
+    fn new(key: &str) -> Self {
+        Self {
+            key: key.to_owned(),
+            value: EntryValue::String(String::new()),
+            description: None,
+            setting_type: None,
+            default_value: None,
+            acceptable_values: None,
+            multiple: false,
+        }
+    }
+
+    /// Converts a raw value into its typed form, failing if it doesn't match the
+    /// entry's setting type or acceptable values.
+    pub fn parse_value(&self, value: &str) -> Result<EntryValue> {
+        let value = value.trim();
+
+        let typed = match self.setting_type.as_deref() {
+            Some("Boolean") => EntryValue::Boolean(
+                value.to_lowercase().parse().map_err(|_| eyre!("'{}' is not a boolean", value))?
+            ),
+            Some("Byte" | "SByte" | "Int16" | "UInt16" | "Int32" | "UInt32" | "Int64" | "UInt64") => {
+                EntryValue::Integer(value.parse().map_err(|_| eyre!("'{}' is not an integer", value))?)
+            }
+            Some("Single" | "Double" | "Decimal") => {
+                EntryValue::Float(value.parse().map_err(|_| eyre!("'{}' is not a number", value))?)
+            }
+            _ => EntryValue::String(value.to_owned()),
+        };
+
+        match (&self.acceptable_values, &typed) {
+            (Some(AcceptableValues::Range { min, max }), EntryValue::Integer(number)) => {
+                ensure!(
+                    (*min..=*max).contains(&(*number as f64)),
+                    "{} must be between {} and {}", self.key, min, max
+                );
+            }
+            (Some(AcceptableValues::Range { min, max }), EntryValue::Float(number)) => {
+                ensure!((*min..=*max).contains(number), "{} must be between {} and {}", self.key, min, max);
+            }
+            (Some(AcceptableValues::List { values }), _) => {
+                let mut chosen = match self.multiple {
+                    true => value.split(',').map(str::trim).collect::<Vec<_>>(),
+                    false => vec![value],
+                };
+
+                chosen.retain(|x| !x.is_empty());
+
+                for choice in chosen {
+                    ensure!(
+                        values.iter().any(|x| x == choice),
+                        "'{}' is not an acceptable value for {}", choice, self.key
+                    );
+                }
+            }
+            _ => {}
+        }
+
+        Ok(typed)
+    }
+}
+
+/// Parses the contents of a `.cfg` file into its sections and entries. Entries
+/// outside of a section are ignored, as BepInEx never writes them.
+pub fn parse(contents: &str) -> Vec<ConfigSection> {
+    let mut sections: Vec<ConfigSection> = Vec::new();
+    let mut pending = ConfigEntry::new("");
+
+    for line in contents.lines() {
+        let trimmed = line.trim();
+
+        if let Some(name) = section_name(trimmed) {
+            sections.push(ConfigSection { name: name.to_owned(), entries: Vec::new() });
+            pending = ConfigEntry::new("");
+        }
+        else if let Some(description) = trimmed.strip_prefix("##") {
+            let description = description.trim();
+
+            pending.description = Some(match pending.description.take() {
+                Some(existing) => format!("{}\n{}", existing, description),
+                None => description.to_owned(),
+            });
+        }
+        else if let Some(comment) = trimmed.strip_prefix('#') {
+            parse_comment(&mut pending, comment.trim());
+        }
+        else if let Some((key, value)) = entry(trimmed)
+            && let Some(section) = sections.last_mut()
+        {
+            let mut new_entry = std::mem::replace(&mut pending, ConfigEntry::new(""));
+
+            new_entry.key = key.to_owned();
+            new_entry.value = new_entry.parse_value(value)
+                .unwrap_or_else(|_| EntryValue::String(value.to_owned()));
+
+            section.entries.push(new_entry);
+        }
+    }
+
+    sections
+}
+
+fn parse_comment(entry: &mut ConfigEntry, comment: &str) {
+    if let Some(setting_type) = comment.strip_prefix("Setting type:") {
+        entry.setting_type = Some(setting_type.trim().to_owned());
+    }
+    else if let Some(default_value) = comment.strip_prefix("Default value:") {
+        entry.default_value = Some(default_value.trim().to_owned());
+    }
+    else if let Some(values) = comment.strip_prefix("Acceptable values:") {
+        let values = values.split(',').map(|x| x.trim().to_owned()).collect();
+
+        entry.acceptable_values = Some(AcceptableValues::List { values });
+    }
+    else if let Some(range) = comment.strip_prefix("Acceptable value range:")
+        && let Some((min, max)) = range.trim().trim_start_matches("From ").split_once(" to ")
+        && let (Ok(min), Ok(max)) = (min.trim().parse(), max.trim().parse())
+    {
+        entry.acceptable_values = Some(AcceptableValues::Range { min, max });
+    }
+    else if comment.starts_with("Multiple values can be set") {
+        entry.multiple = true;
+    }
+}
+
+/// Returns the path of a config file within the [`Collection`]'s config directory,
+/// rejecting paths that would escape it.
+pub fn file_path(collection: &Collection, file: &str) -> Result<PathBuf> {
+    ensure!(
+        Path::new(file).components().all(|x| matches!(x, Component::Normal(_))),
+        "config file '{}' must be a relative path within the config directory",
+        file
+    );
+
+    Ok(config_dir(collection).join(file))
+}
+
+/// Reads a config file, missing files are treated as empty.
+pub async fn read_file(collection: &Collection, file: &str) -> Result<String> {
+    let path = file_path(collection, file)?;
+
+    if !path.exists() {
+        return Ok(String::new());
+    }
+
+    Ok(tokio::fs::read_to_string(path).await?)
+}
+
+/// Parses every `.cfg` file in the [`Collection`]'s config directory.
+pub async fn list_files(collection: &Collection) -> Result<Vec<ConfigFile>> {
+    let config_dir = config_dir(collection);
+
+    if !config_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut files = Vec::new();
+
+    for entry in fs::iterate_directory(&config_dir).await? {
+        let path = entry.path();
+
+        if !path.is_file() || path.extension().is_none_or(|x| x != "cfg") {
+            continue;
+        }
+
+        let file = path.strip_prefix(&config_dir)?
+            .components()
+            .map(|x| x.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        let contents = tokio::fs::read_to_string(&path).await?;
+
+        files.push(ConfigFile { file, sections: parse(&contents) });
+    }
+
+    files.sort_by(|a, b| a.file.cmp(&b.file));
+
+    Ok(files)
+}
+
+/// Validates and writes a single entry of an existing config file.
+pub async fn set_entry(collection: &Collection, file: &str, section: &str, key: &str, value: &str) -> Result<()> {
+    let path = file_path(collection, file)?;
+
+    ensure!(path.exists(), "config file '{}' does not exist", file);
+
+    let contents = tokio::fs::read_to_string(&path).await?;
+
+    let Some(existing) = parse(&contents)
+        .into_iter()
+        .filter(|x| x.name == section)
+        .flat_map(|x| x.entries)
+        .find(|x| x.key == key)
+    else {
+        bail!("config file '{}' has no entry '{}' in section '{}'", file, key, section);
+    };
+
+    existing.parse_value(value)?;
+
+    tokio::fs::write(&path, set_value(&contents, section, key, value.trim())).await?;
+
+    Ok(())
+}
+
+/// Copies the config files shipped in a plugin's cache directory over the
+/// [`Collection`]'s copies, backing up any file that would be overwritten. Returns
+/// the restored files relative to the collection directory.
+pub async fn restore_defaults(collection: &Collection, plugin_cache: &Path) -> Result<Vec<PathBuf>> {
+    let collection_dir = paths::collection_dir(&collection.id);
+    let backup_dir = paths::config_backup_dir(
+        &collection.id,
+        &chrono::Utc::now().format("%Y%m%dT%H%M%S%.3f").to_string(),
+    );
+
+    let mut restored = Vec::new();
+
+    for entry in fs::iterate_directory(plugin_cache).await? {
+        let default_path = entry.path();
+        let rel_path = default_path.strip_prefix(plugin_cache)?;
+
+        let is_config = rel_path.starts_with(collection.game.mod_loader.config_dir())
+            || rel_path.extension().is_some_and(|x| x == "cfg");
+
+        if !default_path.is_file() || !is_config {
+            continue;
+        }
+
+        restore_file(&default_path, &collection_dir.join(rel_path), &backup_dir.join(rel_path)).await?;
+
+        restored.push(rel_path.to_path_buf());
+    }
+
+    if backup_dir.exists() {
+        tracing::info!("backed up overwritten config files to {}", backup_dir.display());
+    }
+
+    Ok(restored)
+}
+
+/// Replaces the file at `target_path` with a copy of `default_path`, first backing
+/// the existing file up to `backup_path`.
+async fn restore_file(default_path: &Path, target_path: &Path, backup_path: &Path) -> Result<()> {
+    if target_path.exists() {
+        tokio::fs::create_dir_all(backup_path.parent().unwrap()).await?;
+        tokio::fs::copy(target_path, backup_path).await?;
+
+        // The existing file may be hard linked to the cached default, copying over
+        // it would truncate the default as well.
+        tokio::fs::remove_file(target_path).await?;
+    }
+    else {
+        tokio::fs::create_dir_all(target_path.parent().unwrap()).await?;
+    }
+
+    // Copied rather than linked so edits don't change the cached default.
+    tokio::fs::copy(default_path, target_path).await?;
+
+    Ok(())
+}
+
+fn config_dir(collection: &Collection) -> PathBuf {
+    paths::collection_dir(&collection.id).join(collection.game.mod_loader.config_dir())
+}
+
+/// Returns the value of `key` within `section`, if it exists.
+pub fn get_value<'a>(contents: &'a str, section: &str, key: &str) -> Option<&'a str> {
+    let mut current_section = None;
+
+    for line in contents.lines() {
+        let trimmed = line.trim();
+
+        if let Some(name) = section_name(trimmed) {
+            current_section = Some(name);
+        }
+        else if current_section == Some(section)
+            && let Some((entry_key, value)) = entry(trimmed)
+            && entry_key == key
+        {
+            return Some(value);
+        }
+    }
+
+    None
+}
+
+/// Sets the value of `key` within `section`, adding the entry or section if they
+/// don't exist yet. Returns the new contents of the file.
+pub fn set_value(contents: &str, section: &str, key: &str, value: &str) -> String {
+    add_entry(contents, section, key, value, &[])
+}
+
+/// Same as [`set_value`], but a newly added entry is written below the provided
+/// comment lines, separated from the previous entry by an empty line.
+fn add_entry(contents: &str, section: &str, key: &str, value: &str, comments: &[&str]) -> String {
+    let line_ending = if contents.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut lines = contents.lines().map(str::to_owned).collect::<Vec<_>>();
+
+    let mut current_section = None;
+    let mut section_end = None;
+
+    for (index, line) in lines.iter_mut().enumerate() {
+        let trimmed = line.trim();
+
+        if let Some(name) = section_name(trimmed) {
+            current_section = Some(name.to_owned());
+
+            continue;
+        }
+
+        if current_section.as_deref() != Some(section) {
+            continue;
+        }
+
+        if let Some((entry_key, _)) = entry(trimmed)
+            && entry_key == key
+        {
+            *line = format!("{} = {}", key, value);
+
+            return lines.join(line_ending) + line_ending;
+        }
+
+        if !trimmed.is_empty() {
+            section_end = Some(index + 1);
+        }
+    }
+
+    let mut new_lines = comments.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+    new_lines.push(format!("{} = {}", key, value));
+
+    match section_end {
+        Some(index) => {
+            if !comments.is_empty() {
+                new_lines.insert(0, String::new());
+            }
+
+            lines.splice(index..index, new_lines);
+        }
+        None => {
+            if lines.last().is_some_and(|x| !x.trim().is_empty()) {
+                lines.push(String::new());
+            }
+
+            lines.push(format!("[{}]", section));
+            lines.push(String::new());
+            lines.extend(new_lines);
+        }
+    }
+
+    lines.join(line_ending) + line_ending
+}
+
+/// Replaces the `# Default value:` comment above `key` within `section`, entries
+/// without one are left as they are.
+fn set_default_comment(contents: &str, section: &str, key: &str, comment: &str) -> String {
+    let line_ending = if contents.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut lines = contents.lines().map(str::to_owned).collect::<Vec<_>>();
+
+    let mut current_section = None;
+    let mut default_line = None;
+
+    for (index, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+
+        if let Some(name) = section_name(trimmed) {
+            current_section = Some(name);
+            default_line = None;
+        }
+        else if trimmed.starts_with("# Default value:") {
+            default_line = Some(index);
+        }
+        else if let Some((entry_key, _)) = entry(trimmed) {
+            if current_section == Some(section) && entry_key == key {
+                break;
+            }
+
+            default_line = None;
+        }
+        else if trimmed.is_empty() {
+            default_line = None;
+        }
+    }
+
+    let Some(index) = default_line else {
+        return contents.to_owned();
+    };
+
+    lines[index] = comment.to_owned();
+
+    lines.join(line_ending) + line_ending
+}
+
+/// An entry that both the user and the new default changed to different values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeConflict {
+    pub section: String,
+    pub key: String,
+    pub user_value: String,
+    pub default_value: String,
+}
+
+/// Three-way merges a plugin's new default config into the user's copy, using the
+/// previous default as the common base. New entries are added along with their
+/// comments, entries the user never touched follow the new default and changed
+/// entries keep the user's value. The `# Default value:` comment of every kept entry
+/// follows the new default. Returns the merged contents along with any entries that
+/// conflicted.
+pub fn merge(base: &str, user: &str, new_default: &str) -> (String, Vec<MergeConflict>) {
+    let mut merged = user.to_owned();
+    let mut conflicts = Vec::new();
+    let mut current_section = None;
+    let mut comments = Vec::new();
+
+    for line in new_default.lines() {
+        let trimmed = line.trim();
+
+        if let Some(name) = section_name(trimmed) {
+            current_section = Some(name);
+            comments.clear();
+
+            continue;
+        }
+
+        if trimmed.starts_with('#') {
+            comments.push(trimmed);
+
+            continue;
+        }
+
+        let comments = std::mem::take(&mut comments);
+
+        let (Some(section), Some((key, new_value))) = (current_section, entry(trimmed)) else {
+            continue;
+        };
+
+        let base_value = get_value(base, section, key);
+
+        let Some(user_value) = get_value(user, section, key) else {
+            // Only add entries that are actually new, the user may have removed others.
+            if base_value.is_none() {
+                merged = add_entry(&merged, section, key, new_value, &comments);
+            }
+
+            continue;
+        };
+
+        if let Some(comment) = comments.iter().find(|x| x.starts_with("# Default value:")) {
+            merged = set_default_comment(&merged, section, key, comment);
+        }
+
+        if user_value == new_value {
+            continue;
+        }
+
+        match base_value {
+            Some(base_value) if base_value == user_value => {
+                merged = set_value(&merged, section, key, new_value);
+            }
+            Some(base_value) if base_value != new_value => conflicts.push(MergeConflict {
+                section: section.to_owned(),
+                key: key.to_owned(),
+                user_value: user_value.to_owned(),
+                default_value: new_value.to_owned(),
+            }),
+            _ => {}
+        }
+    }
+
+    (merged, conflicts)
+}
+
+fn section_name(line: &str) -> Option<&str> {
+    line.strip_prefix('[')?.strip_suffix(']').map(str::trim)
+}
+
+fn entry(line: &str) -> Option<(&str, &str)> {
+    if line.starts_with('#') {
+        return None;
+    }
+
+    let (key, value) = line.split_once('=')?;
+
+    Some((key.trim(), value.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "## Settings file was created by plugin Example v1.0.0\n\
+        \n\
+        [General]\n\
+        \n\
+        ## Enables the plugin\n\
+        # Setting type: Boolean\n\
+        # Default value: true\n\
+        Enabled = true\n\
+        \n\
+        [Logging.Console]\n\
+        \n\
+        Enabled = false\n";
+
+    const TYPED_CONFIG: &str = "[General]\n\
+        \n\
+        ## Volume of the\n\
+        ## example sound\n\
+        # Setting type: Single\n\
+        # Default value: 0.5\n\
+        # Acceptable value range: From 0 to 1\n\
+        Volume = 0.75\n\
+        \n\
+        ## Which messages to log\n\
+        # Setting type: LogLevel\n\
+        # Default value: Error\n\
+        # Acceptable values: None, Error, Warning, Info\n\
+        # Multiple values can be set at the same time by separating them with , (e.g. Debug, Warning)\n\
+        Levels = Error, Warning\n\
+        \n\
+        Untyped = hello\n";
+
+    #[tokio::test]
+    async fn restore_hard_linked_file() {
+        let dir = std::env::temp_dir().join(format!("katabasis-restore-{}", uuid::Uuid::new_v4()));
+        let default_path = dir.join("cache").join("Example.cfg");
+        let target_path = dir.join("collection").join("Example.cfg");
+        let backup_path = dir.join("backup").join("Example.cfg");
+
+        tokio::fs::create_dir_all(default_path.parent().unwrap()).await.unwrap();
+        tokio::fs::create_dir_all(target_path.parent().unwrap()).await.unwrap();
+        tokio::fs::write(&default_path, CONFIG).await.unwrap();
+        tokio::fs::hard_link(&default_path, &target_path).await.unwrap();
+
+        restore_file(&default_path, &target_path, &backup_path).await.unwrap();
+
+        assert_eq!(tokio::fs::read_to_string(&default_path).await.unwrap(), CONFIG);
+        assert_eq!(tokio::fs::read_to_string(&target_path).await.unwrap(), CONFIG);
+        assert_eq!(tokio::fs::read_to_string(&backup_path).await.unwrap(), CONFIG);
+
+        // Editing the restored file must leave the cached default alone.
+        tokio::fs::write(&target_path, TYPED_CONFIG).await.unwrap();
+        assert_eq!(tokio::fs::read_to_string(&default_path).await.unwrap(), CONFIG);
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+
+    #[test]
+    fn parse_typed_entries() {
+        let sections = parse(TYPED_CONFIG);
+        let entries = &sections[0].entries;
+
+        assert_eq!(sections.len(), 1);
+        assert_eq!(entries.len(), 3);
+
+        assert_eq!(entries[0].value, EntryValue::Float(0.75));
+        assert_eq!(entries[0].description.as_deref(), Some("Volume of the\nexample sound"));
+        assert_eq!(entries[0].default_value.as_deref(), Some("0.5"));
+        assert_eq!(entries[0].acceptable_values, Some(AcceptableValues::Range { min: 0.0, max: 1.0 }));
+
+        assert_eq!(entries[1].setting_type.as_deref(), Some("LogLevel"));
+        assert!(entries[1].multiple);
+
+        assert_eq!(entries[2].value, EntryValue::String("hello".to_owned()));
+        assert_eq!(entries[2].description, None);
+    }
+
+    #[test]
+    fn validate_entry_values() {
+        let sections = parse(TYPED_CONFIG);
+        let entries = &sections[0].entries;
+
+        assert!(entries[0].parse_value("1").is_ok());
+        assert!(entries[0].parse_value("1.5").is_err());
+        assert!(entries[0].parse_value("loud").is_err());
+        assert!(entries[1].parse_value("Info, None").is_ok());
+        assert!(entries[1].parse_value("Debug").is_err());
+    }
+
+    #[test]
+    fn merge_new_default() {
+        let base = "[General]\nA = 1\nB = 1\nC = 1\nD = 1\n";
+        let user = "[General]\n# Edited\nA = 1\nB = 2\nC = 2\nD = 1\n";
+        let new_default = "[General]\nA = 5\nB = 1\nC = 3\nD = 1\nE = 1\n\n[Extra]\nF = 1\n";
+
+        let (merged, conflicts) = merge(base, user, new_default);
+
+        // Untouched entries follow the new default, edited ones keep the user's value.
+        assert_eq!(get_value(&merged, "General", "A"), Some("5"));
+        assert_eq!(get_value(&merged, "General", "B"), Some("2"));
+        assert_eq!(get_value(&merged, "General", "C"), Some("2"));
+        assert_eq!(get_value(&merged, "General", "E"), Some("1"));
+        assert_eq!(get_value(&merged, "Extra", "F"), Some("1"));
+        assert!(merged.contains("# Edited\n"));
+
+        assert_eq!(conflicts, vec![MergeConflict {
+            section: "General".to_owned(),
+            key: "C".to_owned(),
+            user_value: "2".to_owned(),
+            default_value: "3".to_owned(),
+        }]);
+    }
+
+    #[test]
+    fn merge_new_default_comments() {
+        let base = "[General]\n\n# Setting type: Int32\n# Default value: 1\nCount = 1\n";
+        let user = "[General]\n\n# Setting type: Int32\n# Default value: 1\nCount = 4\n";
+        let new_default = "[General]\n\n\
+            # Setting type: Int32\n\
+            # Default value: 2\n\
+            Count = 2\n\
+            \n\
+            ## Which messages to log\n\
+            # Setting type: LogLevel\n\
+            # Default value: Error\n\
+            # Acceptable values: None, Error, Warning\n\
+            Level = Error\n";
+
+        let (merged, conflicts) = merge(base, user, new_default);
+        let sections = parse(&merged);
+        let entries = &sections[0].entries;
+
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(entries.len(), 2);
+
+        assert_eq!(entries[0].value, EntryValue::Integer(4));
+        assert_eq!(entries[0].default_value.as_deref(), Some("2"));
+
+        assert_eq!(entries[1].key, "Level");
+        assert_eq!(entries[1].description.as_deref(), Some("Which messages to log"));
+        assert_eq!(entries[1].setting_type.as_deref(), Some("LogLevel"));
+        assert_eq!(entries[1].default_value.as_deref(), Some("Error"));
+        assert_eq!(entries[1].acceptable_values, Some(AcceptableValues::List {
+            values: vec!["None".to_owned(), "Error".to_owned(), "Warning".to_owned()],
+        }));
+        assert!(merged.contains("Count = 4\n\n## Which messages to log\n"));
+    }
+
+    #[test]
+    fn get_values_by_section() {
+        assert_eq!(get_value(CONFIG, "General", "Enabled"), Some("true"));
+        assert_eq!(get_value(CONFIG, "Logging.Console", "Enabled"), Some("false"));
+        assert_eq!(get_value(CONFIG, "General", "Missing"), None);
+    }
+
+    #[test]
+    fn set_existing_value_keeps_comments() {
+        let updated = set_value(CONFIG, "General", "Enabled", "false");
+
+        assert_eq!(get_value(&updated, "General", "Enabled"), Some("false"));
+        assert_eq!(get_value(&updated, "Logging.Console", "Enabled"), Some("false"));
+        assert!(updated.contains("## Enables the plugin\n# Setting type: Boolean\n"));
+    }
+
+    #[test]
+    fn set_new_value_and_section() {
+        let updated = set_value(CONFIG, "General", "Volume", "5");
+        let updated = set_value(&updated, "Extra", "Name", "Example");
+
+        assert_eq!(get_value(&updated, "General", "Volume"), Some("5"));
+        assert_eq!(get_value(&updated, "Extra", "Name"), Some("Example"));
+        assert!(updated.ends_with("[Extra]\n\nName = Example\n"));
+    }
+}
@@ -0,0 +1,318 @@
+//! Finds the plugins most likely responsible for problems in a BepInEx log.
+
+use std::collections::HashMap;
+use eyre::Result;
+use serde::Serialize;
+use crate::collection::Collection;
+use crate::utils::paths;
+
+/// Most lines of an entry kept in an excerpt, long stack traces are cut short.
+const MAX_EXCERPT_LINES: usize = 8;
+
+/// Most findings kept for a single suspect.
+const MAX_FINDINGS: usize = 5;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Suspect {
+    /// The ident of the suspected plugin.
+    pub plugin: String,
+    pub score: u32,
+    pub findings: Vec<Finding>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Finding {
+    pub kind: FindingKind,
+    pub excerpt: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FindingKind {
+    Exception,
+    MissingDependency,
+    FailedToLoad,
+}
+
+impl FindingKind {
+    fn score(&self) -> u32 {
+        match self {
+            FindingKind::MissingDependency | FindingKind::FailedToLoad => 5,
+            FindingKind::Exception => 3,
+        }
+    }
+}
+
+/// Names a plugin may appear as in a log: its package name and the names of its
+/// assemblies, which usually match the root namespace of its stack frames. The
+/// GUID a plugin registers with BepInEx is not known, so isn't included.
+#[derive(Debug)]
+pub struct PluginNames {
+    pub ident: String,
+    pub names: Vec<String>,
+}
+
+impl PluginNames {
+    pub fn new(ident: &str, names: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
+        Self {
+            ident: ident.to_owned(),
+            names: names.into_iter().map(|x| normalise(x.as_ref())).filter(|x| !x.is_empty()).collect(),
+        }
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        let name = normalise(name);
+
+        !name.is_empty() && self.names.contains(&name)
+    }
+}
+
+/// Collects the names each of the [`Collection`]'s plugins may appear as. The mod
+/// loader is left out as it shows up in almost every stack trace.
+pub async fn plugin_names(collection: &Collection) -> Result<Vec<PluginNames>> {
+    let collection_dir = paths::collection_dir(&collection.id);
+    let mod_loader = &collection.game.mod_loader;
+
+    let mut all_names = Vec::new();
+
+    for plugin in &collection.plugins {
+        let ident = plugin.ident();
+
+        if mod_loader.is_loader_plugin(ident.full_name()) {
+            continue;
+        }
+
+        let mut names = vec![ident.name().to_owned()];
+
+        for plugin_dir in mod_loader.plugin_dirs(ident.full_name()) {
+            let plugin_dir = collection_dir.join(plugin_dir);
+
+            if !plugin_dir.exists() {
+                continue;
+            }
+
+            for entry in crate::utils::fs::iterate_directory(&plugin_dir).await? {
+                let path = entry.path();
+
+                // Disabled plugins have an extra extension, so check the whole name.
+                let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+
+                if let Some((stem, _)) = file_name.split_once(".dll") {
+                    names.push(stem.to_owned());
+                }
+            }
+        }
+
+        all_names.push(PluginNames::new(ident.as_str(), names));
+    }
+
+    Ok(all_names)
+}
+
+/// Parses a BepInEx log and returns the plugins suspected of causing problems,
+/// most likely first.
+pub fn analyse(log: &str, plugins: &[PluginNames]) -> Vec<Suspect> {
+    let mut suspects: HashMap<usize, Suspect> = HashMap::new();
+
+    for entry in log_entries(log) {
+        let Some((kind, culprits)) = classify(&entry, plugins) else {
+            continue;
+        };
+
+        let excerpt = entry.iter().take(MAX_EXCERPT_LINES).copied().collect::<Vec<_>>().join("\n");
+
+        for (rank, index) in culprits.into_iter().enumerate() {
+            let suspect = suspects.entry(index).or_insert_with(|| Suspect {
+                plugin: plugins[index].ident.clone(),
+                score: 0,
+                findings: Vec::new(),
+            });
+
+            // Frames further down the stack are less likely to be at fault.
+            suspect.score += match rank {
+                0 => kind.score(),
+                _ => 1,
+            };
+
+            let finding = Finding { kind, excerpt: excerpt.clone() };
+
+            if suspect.findings.len() < MAX_FINDINGS && !suspect.findings.contains(&finding) {
+                suspect.findings.push(finding);
+            }
+        }
+    }
+
+    let mut suspects = suspects.into_values().collect::<Vec<_>>();
+
+    suspects.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.plugin.cmp(&b.plugin)));
+
+    suspects
+}
+
+/// Splits the log into entries, each starting with a `[Level : Source]` header
+/// and including any lines that follow it, such as stack traces.
+fn log_entries(log: &str) -> Vec<Vec<&str>> {
+    let mut entries: Vec<Vec<&str>> = Vec::new();
+
+    for line in log.lines() {
+        if header(line).is_some() || entries.is_empty() {
+            entries.push(vec![line]);
+        }
+        else if let Some(entry) = entries.last_mut() {
+            entry.push(line);
+        }
+    }
+
+    entries
+}
+
+/// Returns the level and message of a log line starting with a BepInEx header.
+fn header(line: &str) -> Option<(&str, &str)> {
+    let (header, message) = line.strip_prefix('[')?.split_once(']')?;
+    let (level, _source) = header.split_once(':')?;
+
+    Some((level.trim(), message.trim()))
+}
+
+/// Works out what kind of problem an entry describes and which plugins are
+/// involved, the most likely culprit first.
+fn classify(entry: &[&str], plugins: &[PluginNames]) -> Option<(FindingKind, Vec<usize>)> {
+    let (level, message) = header(entry[0])?;
+
+    if !matches!(level, "Error" | "Fatal" | "Warning") {
+        return None;
+    }
+
+    let lowercase = message.to_lowercase();
+
+    let kind = if lowercase.contains("missing dependencies") || lowercase.contains("is incompatible with") {
+        FindingKind::MissingDependency
+    }
+    else if lowercase.contains("failed to load")
+        || lowercase.contains("could not load")
+        || lowercase.contains("error loading")
+    {
+        FindingKind::FailedToLoad
+    }
+    else if entry.iter().any(|x| x.contains("Exception")) {
+        FindingKind::Exception
+    }
+    else {
+        return None;
+    };
+
+    let culprits = match kind {
+        // BepInEx names the plugin in brackets, e.g. `Could not load [Example 1.0.0]`.
+        FindingKind::MissingDependency | FindingKind::FailedToLoad => {
+            let name = message.split_once('[')?.1.split_once(']')?.0;
+            let name = name.rsplit_once(' ').map_or(name, |(name, _version)| name);
+
+            plugins.iter().position(|x| x.matches(name)).into_iter().collect()
+        }
+        FindingKind::Exception => {
+            let mut culprits = Vec::new();
+
+            for frame in entry.iter().skip(1).filter_map(|x| frame_namespace(x)) {
+                if let Some(index) = match_namespace(frame, plugins)
+                    && !culprits.contains(&index)
+                {
+                    culprits.push(index);
+                }
+            }
+
+            culprits
+        }
+    };
+
+    (!culprits.is_empty()).then_some((kind, culprits))
+}
+
+/// Returns the fully qualified method of a Mono or Unity style stack frame.
+fn frame_namespace(line: &str) -> Option<&str> {
+    let line = line.trim();
+    let line = line.strip_prefix("at ").unwrap_or(line);
+    let line = line.strip_prefix("(wrapper dynamic-method) ").unwrap_or(line);
+
+    let end = line.find([' ', '(', '[', '<']).unwrap_or(line.len());
+    let method = &line[..end];
+
+    method.contains('.').then_some(method)
+}
+
+/// Matches the leading segments of a namespace against the plugin names, so that
+/// both `Jotunn.Managers` and `ValheimModding.Jotunn.Managers` match `Jotunn`.
+fn match_namespace(namespace: &str, plugins: &[PluginNames]) -> Option<usize> {
+    let segments = namespace.split('.').collect::<Vec<_>>();
+
+    let candidates = (1..=segments.len().min(3))
+        .map(|x| segments[..x].join("."))
+        .chain(segments.iter().take(2).map(|x| x.to_string()));
+
+    for candidate in candidates {
+        if let Some(index) = plugins.iter().position(|x| x.matches(&candidate)) {
+            return Some(index);
+        }
+    }
+
+    None
+}
+
+fn normalise(name: &str) -> String {
+    name.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|x| x.to_ascii_lowercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOG: &str = "[Message:   BepInEx] BepInEx 5.4.2202 - valheim\n\
+        [Info   :   BepInEx] Loading [Jotunn 2.25.0]\n\
+        [Error  :   BepInEx] Could not load [Azu Crafty Boxes 1.8.0] because it has missing dependencies: com.example.missing (v1.0.0 or newer)\n\
+        [Error  : Unity Log] NullReferenceException: Object reference not set to an instance of an object\n\
+        Stack trace:\n\
+        Example.Broken.Patches.PlayerPatch.Postfix (Player __instance) (at <00000000000000000000000000000000>:0)\n\
+        (wrapper dynamic-method) Player.DMD<Player::Update>(Player)\n\
+        Jotunn.Managers.InputManager.Update () (at <00000000000000000000000000000000>:0)\n\
+        [Warning:   BepInEx] Nothing to see here\n";
+
+    fn plugins() -> Vec<PluginNames> {
+        vec![
+            PluginNames::new("ValheimModding-Jotunn-2.25.0", ["Jotunn"]),
+            PluginNames::new("Azumatt-AzuCraftyBoxes-1.8.0", ["AzuCraftyBoxes", "Azu Crafty Boxes"]),
+            PluginNames::new("Example-Broken-1.0.0", ["Broken", "Example.Broken"]),
+        ]
+    }
+
+    #[test]
+    fn rank_suspects() {
+        let suspects = analyse(LOG, &plugins());
+
+        assert_eq!(suspects.len(), 3);
+
+        assert_eq!(suspects[0].plugin, "Azumatt-AzuCraftyBoxes-1.8.0");
+        assert_eq!(suspects[0].findings[0].kind, FindingKind::MissingDependency);
+
+        // The top frame of the exception is blamed over deeper ones.
+        assert_eq!(suspects[1].plugin, "Example-Broken-1.0.0");
+        assert_eq!(suspects[1].score, 3);
+        assert_eq!(suspects[2].plugin, "ValheimModding-Jotunn-2.25.0");
+        assert_eq!(suspects[2].score, 1);
+
+        assert!(suspects[1].findings[0].excerpt.starts_with("[Error  : Unity Log] NullReferenceException"));
+        assert!(suspects[1].findings[0].excerpt.contains("PlayerPatch.Postfix"));
+    }
+
+    #[test]
+    fn parse_stack_frames() {
+        assert_eq!(
+            frame_namespace("  at Jotunn.Managers.ItemManager.Load () [0x00000] in <abc>:0"),
+            Some("Jotunn.Managers.ItemManager.Load")
+        );
+        assert_eq!(frame_namespace("Stack trace:"), None);
+    }
+}
@@ -1,7 +1,8 @@
 use crate::collection::{Collection, Plugin};
 use crate::{event, thunderstore};
+use crate::targets::Target;
 use crate::thunderstore::version::VersionIdent;
-use crate::utils::paths;
+use crate::utils::{fs, paths};
 use eyre::{ensure, Result};
 use std::path::PathBuf;
 
@@ -54,17 +55,113 @@ async fn try_cache_install(collection: &Collection, plugin: &Plugin) -> Result<b
 }
 
 async fn download_to_cache(collection: &Collection, plugin: &Plugin) -> Result<()> {
-    let zip = thunderstore::download_specific_package(plugin.ident()).await?;
+    ensure_cached(collection, plugin.ident()).await?;
 
-    let installer = collection.game
+    Ok(())
+}
+
+/// Returns the plugin's cache directory, downloading it first if it isn't cached.
+pub async fn ensure_cached(collection: &Collection, ident: &VersionIdent) -> Result<PathBuf> {
+    let cache_dir = cache_path(ident);
+
+    if !cache_dir.exists() {
+        let bytes = thunderstore::download_specific_package(ident).await?;
+        let hash = fs::hash_bytes(&bytes);
+
+        extract_to_cache(collection.game, ident, bytes, &hash).await?;
+    }
+
+    Ok(cache_dir)
+}
+
+/// Returns the SHA-256 hash of the plugin's archive. Plugins cached before hashes
+/// were recorded are downloaded again to work it out.
+pub async fn archive_hash(collection: &Collection, ident: &VersionIdent) -> Result<String> {
+    if let Ok(hash) = tokio::fs::read_to_string(hash_path(ident)).await {
+        return Ok(hash.trim().to_owned());
+    }
+
+    let bytes = thunderstore::download_specific_package(ident).await?;
+    let hash = fs::hash_bytes(&bytes);
+
+    if cache_path(ident).exists() {
+        tokio::fs::write(hash_path(ident), &hash).await?;
+    }
+    else {
+        extract_to_cache(collection.game, ident, bytes, &hash).await?;
+    }
+
+    Ok(hash)
+}
+
+/// Makes sure the plugin is in the cache and that its archive matches the expected
+/// hash, downloading it from `download_url` if required. Fails if the hashes differ.
+pub async fn cache_verified(
+    target: Target,
+    ident: &VersionIdent,
+    download_url: &str,
+    expected_hash: &str,
+) -> Result<()> {
+    let cached_hash = tokio::fs::read_to_string(hash_path(ident)).await.ok();
+
+    if let Some(hash) = cached_hash
+        && cache_path(ident).exists()
+    {
+        ensure!(
+            hash.trim() == expected_hash,
+            "archive hash mismatch for '{}', expected {} but the cached archive is {}",
+            ident.as_str(),
+            expected_hash,
+            hash.trim()
+        );
+
+        return Ok(());
+    }
+
+    let bytes = thunderstore::download_archive(download_url).await?;
+    let hash = fs::hash_bytes(&bytes);
+
+    ensure!(
+        hash == expected_hash,
+        "archive hash mismatch for '{}', expected {} but downloaded {} from {}",
+        ident.as_str(),
+        expected_hash,
+        hash,
+        download_url
+    );
+
+    // The existing cache entry can't be verified, replace it with the verified archive.
+    if cache_path(ident).exists() {
+        tokio::fs::remove_dir_all(cache_path(ident)).await?;
+    }
+
+    extract_to_cache(target, ident, bytes, &hash).await
+}
+
+/// Extracts the archive into the plugin cache and records its hash alongside it.
+async fn extract_to_cache(
+    target: Target,
+    ident: &VersionIdent,
+    bytes: Vec<u8>,
+    hash: &str,
+) -> Result<()> {
+    let installer = target
         .mod_loader
-        .installer_for_plugin(plugin.ident().full_name());
+        .installer_for_plugin(ident.full_name());
 
-    installer.extract(zip, cache_path(plugin.ident()), plugin.ident().full_name()).await?;
+    installer.extract(thunderstore::open_archive(bytes)?, cache_path(ident), ident.full_name()).await?;
+
+    tokio::fs::write(hash_path(ident), hash).await?;
 
     Ok(())
 }
 
-fn cache_path(ident: &VersionIdent) -> PathBuf {
+/// Returns the directory a plugin is extracted to in the cache, laid out the same
+/// way it is installed within a collection.
+pub fn cache_path(ident: &VersionIdent) -> PathBuf {
     paths::plugin_cache_dir().join(ident.as_str())
 }
+
+fn hash_path(ident: &VersionIdent) -> PathBuf {
+    paths::plugin_cache_dir().join(format!("{}.sha256", ident.as_str()))
+}
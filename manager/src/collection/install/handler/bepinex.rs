@@ -27,7 +27,7 @@ impl PluginHandler for BepInExHandler {
     }
 
     async fn install(&self, src: &Path, _plugin_name: &str, collection: &Collection) -> eyre::Result<()> {
-        let dest = paths::collection_dir(&collection.name);
+        let dest = paths::collection_dir(&collection.id);
 
         if !dest.exists() {
             tokio::fs::create_dir_all(&dest).await?;
@@ -63,7 +63,7 @@ impl PluginHandler for BepInExHandler {
 }
 
 async fn walk_bepinex_core_dir(collection: &Collection) -> eyre::Result<Vec<PathBuf>> {
-    let dest = paths::collection_dir(&collection.name)
+    let dest = paths::collection_dir(&collection.id)
         .join("BepInEx")
         .join("core");
 
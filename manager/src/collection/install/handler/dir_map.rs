@@ -60,11 +60,11 @@ impl<'a> MappedInstaller<'a> {
         }
     }
 
-    fn maps(&self) -> impl Iterator<Item = &DirectoryMap> {
+    fn maps(&self) -> impl Iterator<Item = &DirectoryMap<'_>> {
         self.dir_maps.iter()
     }
 
-    fn match_map(&self, name: &str) -> Option<&DirectoryMap> {
+    fn match_map(&self, name: &str) -> Option<&DirectoryMap<'_>> {
         self.maps().find(|map| map.dir_name.to_lowercase() == name.to_lowercase())
     }
 
@@ -81,10 +81,10 @@ impl<'a> MappedInstaller<'a> {
                 Some(Component::Normal(name)) => {
                     alt_path.push(name);
 
-                    if let Some(name) = name.to_str() {
-                        if let Some(map) = self.match_map(name) {
-                            break map;
-                        }
+                    if let Some(name) = name.to_str()
+                        && let Some(map) = self.match_map(name)
+                    {
+                        break map;
                     }
                 }
                 Some(Component::ParentDir) => { alt_path.pop(); }
@@ -112,6 +112,25 @@ impl<'a> MappedInstaller<'a> {
         Ok(Some(Cow::Owned(target_path)))
     }
 
+    /// Returns the directories, relative to the collection directory, that belong
+    /// solely to the plugin.
+    pub fn plugin_dirs(&self, plugin_name: &str) -> Vec<PathBuf> {
+        self.maps()
+            .filter(|map| map.mode == MapMode::SeparateDir)
+            .map(|map| Path::new(map.dir_path).join(plugin_name))
+            .collect()
+    }
+
+    /// Returns the paths within the collection that belong solely to the plugin.
+    fn mod_paths(&self, plugin: &Plugin, collection: &Collection) -> Vec<PathBuf> {
+        let collection_dir = paths::collection_dir(&collection.id);
+
+        self.plugin_dirs(plugin.kind.full_name())
+            .into_iter()
+            .map(|dir| collection_dir.join(dir))
+            .collect()
+    }
+
     async fn for_mod_file<F>(
         &self,
         plugin: &Plugin,
@@ -121,19 +140,8 @@ impl<'a> MappedInstaller<'a> {
     where
         F: AsyncFnMut(&Path) -> Result<()>
     {
-        let package_name = plugin.kind.full_name();
-
-        for map in self.maps() {
-            match map.mode {
-                MapMode::SeparateDir => {
-                    let path = paths::collection_dir(&collection.name)
-                        .join(&map.dir_path)
-                        .join(package_name);
-
-                    on_file(&path).await?;
-                }
-                MapMode::None => {}
-            }
+        for path in self.mod_paths(plugin, collection) {
+            on_file(&path).await?;
         }
 
         Ok(())
@@ -153,7 +161,7 @@ impl PluginHandler for MappedInstaller<'_> {
     }
 
     async fn install(&self, src: &Path, _plugin_name: &str, collection: &Collection) -> Result<()> {
-        let dest = paths::collection_dir(&collection.name);
+        let dest = paths::collection_dir(&collection.id);
 
         fs::copy_dir_contents_to(src, dest, |rel_path, _| -> Result<CopyFileOpts> {
             let mapped_dir = self
@@ -161,9 +169,10 @@ impl PluginHandler for MappedInstaller<'_> {
                 .find(|map| rel_path.starts_with(map.dir_path))
                 .ok_or_eyre("failed to find mapped dir, this should be impossible")?;
 
+            // Mutable files like configs belong to the user once installed.
             let should_overwrite = match mapped_dir.mode {
                 MapMode::SeparateDir => false,
-                MapMode::None => true,
+                MapMode::None => !mapped_dir.files_mutable,
             };
 
             Ok(CopyFileOpts { should_copy_file: mapped_dir.files_mutable, should_overwrite_file: should_overwrite })
@@ -174,6 +183,10 @@ impl PluginHandler for MappedInstaller<'_> {
 
     async fn uninstall(&self, plugin: &Plugin, collection: &Collection) -> Result<()> {
         self.for_mod_file(plugin, collection, async |path| -> Result<()> {
+            if !path.exists() {
+                return Ok(());
+            }
+
             if path.is_file() {
                 tokio::fs::remove_file(path).await?;
             }
@@ -188,6 +201,20 @@ impl PluginHandler for MappedInstaller<'_> {
     }
 
     async fn switch(&self, enabled: bool, plugin: &Plugin, collection: &Collection) -> Result<()> {
-        todo!()
+        for path in self.mod_paths(plugin, collection).into_iter().filter(|x| x.exists()) {
+            if path.is_file() {
+                fs::switch_file(&path, enabled).await?;
+
+                continue;
+            }
+
+            for entry in fs::iterate_directory(&path).await? {
+                if entry.file_type().await?.is_file() {
+                    fs::switch_file(entry.path(), enabled).await?;
+                }
+            }
+        }
+
+        Ok(())
     }
 }
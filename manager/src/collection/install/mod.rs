@@ -1,8 +1,13 @@
-use crate::collection::{Collection, Plugin};
-use eyre::{ensure, Result};
+use crate::collection::{config, Collection, InstallReason, Plugin};
+use crate::event::{self, Event};
+use eyre::{ensure, eyre, Result};
 use iter_tools::Itertools;
+use std::collections::HashSet;
+use crate::targets::ModLoader;
 use crate::thunderstore;
+use crate::thunderstore::models::PackageVersion;
 use crate::thunderstore::version::{PackageIdent, VersionIdent};
+use crate::utils::{fs, paths};
 
 pub mod downloader;
 pub mod handler;
@@ -12,7 +17,18 @@ pub async fn install_with_deps(
     plugin_url: &str,
 ) -> Result<()> {
     let package_ident = PackageIdent::from_url(plugin_url)?;
-    let package = thunderstore::query_latest_package(&package_ident).await?;
+
+    install_package(collection, &package_ident, None).await
+}
+
+/// Installs a package and all of its dependencies, using the latest version unless
+/// a specific version is provided. The package is marked as explicitly installed.
+pub async fn install_package(
+    collection: &mut Collection,
+    package_ident: &PackageIdent,
+    version: Option<&str>,
+) -> Result<()> {
+    let package = thunderstore::query_latest_package(package_ident).await?;
 
     ensure!(
         package.supports_target(&collection.game.slug),
@@ -21,12 +37,41 @@ pub async fn install_with_deps(
         collection.game.slug
     );
 
-    let all_plugins = fetch_all_plugins(&package.latest.ident.as_package_ident()).await?;
+    let requested_ident = package.latest.ident.as_package_ident();
+
+    let requested_version = match version {
+        Some(version) => {
+            let ident = VersionIdent::new(requested_ident.namespace(), requested_ident.name(), version);
+
+            thunderstore::query_specific_package(&ident).await?
+        }
+        None => package.latest,
+    };
+
+    let all_plugins = fetch_all_plugins(requested_version).await?;
+
+    // Installing a plugin that was previously only a dependency marks it as explicit.
+    for plugin in collection.plugins.iter_mut() {
+        if plugin.ident().as_package_ident() == requested_ident {
+            plugin.reason = InstallReason::Explicit;
+        }
+    }
+
     let all_current_plugins = &collection.plugins.iter().map(|x| x.ident()).collect::<Vec<_>>();
 
     let all_plugins = all_plugins.into_iter()
-        .filter(|plugin| !all_current_plugins.contains(&plugin))
-        .map(Plugin::from_moved_ident)
+        .filter(|(plugin, _)| !all_current_plugins.contains(&plugin))
+        .map(|(plugin, dependencies)| {
+            let reason = if plugin.as_package_ident() == requested_ident {
+                InstallReason::Explicit
+            } else {
+                InstallReason::Dependency
+            };
+
+            Plugin::from_moved_ident(plugin)
+                .with_reason(reason)
+                .with_dependencies(dependencies)
+        })
         .collect::<Vec<_>>();
 
     downloader::install_plugins(collection, &all_plugins).await?;
@@ -40,7 +85,7 @@ pub async fn install_without_deps(
     collection: &mut Collection,
     idents: &[VersionIdent],
 ) -> Result<()> {
-    let new_plugins = idents.into_iter()
+    let new_plugins = idents.iter()
         .map(Plugin::from_ident)
         .collect::<Vec<_>>();
 
@@ -51,21 +96,242 @@ pub async fn install_without_deps(
     Ok(())
 }
 
-/// Fetches a package based on its [`PackageIdent`] as well as all its dependencies.
-async fn fetch_all_plugins(ident: &PackageIdent) -> Result<Vec<VersionIdent>> {
-    let mut all_plugins: Vec<VersionIdent> = vec![];
-    let package = thunderstore::query_latest_package(ident).await?;
+/// Replaces the [`Plugin`] at the provided index with another version of the same
+/// package, the latest unless a version is provided. Keeps the plugin's install
+/// reason and enabled state.
+pub async fn update_plugin(collection: &mut Collection, index: usize, version: Option<&str>) -> Result<()> {
+    let old_plugin = uninstall_plugin(collection, index).await?;
+    let package_ident = old_plugin.ident().as_package_ident();
+
+    install_package(collection, &package_ident, version).await?;
+
+    let new_index = collection.plugins
+        .iter()
+        .position(|x| x.ident().as_package_ident() == package_ident)
+        .ok_or_else(|| eyre!("failed to find updated plugin '{}'", package_ident.as_str()))?;
+
+    collection.plugins[new_index].reason = old_plugin.reason;
+
+    let new_ident = collection.plugins[new_index].ident().clone();
+
+    merge_config_defaults(collection, old_plugin.ident(), &new_ident).await?;
+
+    if !old_plugin.enabled {
+        switch_plugin(collection, new_index, false).await?;
+    }
+
+    Ok(())
+}
+
+/// Merges the config defaults of every plugin that was replaced by another version
+/// of the same package, given the plugins that were uninstalled to make way for them.
+pub async fn merge_replaced_defaults(collection: &Collection, replaced: &[Plugin]) -> Result<()> {
+    for old_plugin in replaced {
+        let package_ident = old_plugin.ident().as_package_ident();
+
+        let new_plugin = collection.plugins
+            .iter()
+            .find(|x| x.ident().as_package_ident() == package_ident);
+
+        if let Some(new_plugin) = new_plugin {
+            merge_config_defaults(collection, old_plugin.ident(), new_plugin.ident()).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Installing never overwrites existing config files, so merge the new version's
+/// default configs into the user's copies. Conflicts are reported as events.
+async fn merge_config_defaults(collection: &Collection, old: &VersionIdent, new: &VersionIdent) -> Result<()> {
+    let new_cache = downloader::cache_path(new);
+    let old_cache = downloader::cache_path(old);
+
+    if old == new || !new_cache.exists() {
+        return Ok(());
+    }
+
+    for entry in fs::iterate_directory(&new_cache).await? {
+        let new_default_path = entry.path();
+
+        if !new_default_path.is_file() || new_default_path.extension().is_none_or(|x| x != "cfg") {
+            continue;
+        }
+
+        let rel_path = new_default_path.strip_prefix(&new_cache)?;
+        let user_path = paths::collection_dir(&collection.id).join(rel_path);
+
+        if !user_path.exists() {
+            continue;
+        }
+
+        // Without the old default every difference is treated as a user change.
+        let base = tokio::fs::read_to_string(old_cache.join(rel_path)).await.unwrap_or_default();
+        let user = tokio::fs::read_to_string(&user_path).await?;
+        let new_default = tokio::fs::read_to_string(&new_default_path).await?;
+
+        let (merged, conflicts) = config::merge(&base, &user, &new_default);
+
+        if merged != user {
+            tokio::fs::write(&user_path, merged).await?;
+        }
+
+        for conflict in conflicts {
+            event::emit(Event::ConfigConflict {
+                collection: collection.name.clone(),
+                plugin: new.as_str().to_owned(),
+                file: rel_path.display().to_string(),
+                section: conflict.section,
+                key: conflict.key,
+                user_value: conflict.user_value,
+                default_value: conflict.default_value,
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Enables or disables the [`Plugin`] at the provided index.
+pub async fn switch_plugin(collection: &mut Collection, index: usize, enabled: bool) -> Result<()> {
+    let plugin = &collection.plugins[index];
+
+    if plugin.enabled == enabled {
+        return Ok(());
+    }
+
+    let installer = collection.game
+        .mod_loader
+        .installer_for_plugin(plugin.ident().full_name());
+
+    installer.switch(enabled, plugin, collection).await?;
+
+    collection.plugins[index].enabled = enabled;
+
+    Ok(())
+}
+
+/// Removes the [`Plugin`] at the provided index from the collection, deleting
+/// its files from disk. Returns the removed plugin.
+pub async fn uninstall_plugin(collection: &mut Collection, index: usize) -> Result<Plugin> {
+    let plugin = &collection.plugins[index];
+
+    let installer = collection.game
+        .mod_loader
+        .installer_for_plugin(plugin.ident().full_name());
+
+    installer.uninstall(plugin, collection).await?;
+
+    Ok(collection.plugins.remove(index))
+}
+
+/// Returns the indices of every [`Plugin`] that was only installed as a dependency
+/// and is no longer required by any of the remaining plugins. The mod loader is
+/// never considered an orphan.
+pub fn orphaned_plugins(plugins: &[Plugin], mod_loader: &ModLoader) -> Vec<usize> {
+    let mut orphans: HashSet<usize> = HashSet::new();
 
-    all_plugins.push(package.latest.ident);
+    // Removing an orphan can orphan its own dependencies, so keep going until nothing changes.
+    loop {
+        let required = plugins
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !orphans.contains(index))
+            .flat_map(|(_, plugin)| plugin.dependencies.iter().map(PackageIdent::as_str))
+            .collect::<HashSet<_>>();
 
-    for dependencies in package.latest.dependencies {
-        let dependent_dependencies = Box::pin(fetch_all_plugins(&dependencies.as_package_ident())).await?;
+        let new_orphans = plugins
+            .iter()
+            .enumerate()
+            .filter(|(index, plugin)| {
+                !orphans.contains(index)
+                    && plugin.reason == InstallReason::Dependency
+                    && !mod_loader.is_loader_plugin(plugin.ident().full_name())
+                    && !required.contains(plugin.ident().full_name())
+            })
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+
+        if new_orphans.is_empty() {
+            break;
+        }
+
+        orphans.extend(new_orphans);
+    }
+
+    orphans.into_iter().sorted().collect()
+}
+
+/// Fetches all dependencies of a package version, dependencies always use their latest
+/// version. Each plugin is returned alongside the packages it directly depends on.
+async fn fetch_all_plugins(package: PackageVersion) -> Result<Vec<(VersionIdent, Vec<PackageIdent>)>> {
+    let mut all_plugins: Vec<(VersionIdent, Vec<PackageIdent>)> = vec![];
+
+    let dependencies = package.dependencies
+        .iter()
+        .map(VersionIdent::as_package_ident)
+        .collect();
+
+    all_plugins.push((package.ident, dependencies));
+
+    for dependencies in package.dependencies {
+        let dependency = thunderstore::query_latest_package(&dependencies.as_package_ident()).await?;
+        let dependent_dependencies = Box::pin(fetch_all_plugins(dependency.latest)).await?;
 
         all_plugins.extend(dependent_dependencies);
     }
 
     Ok(all_plugins.into_iter()
-        .unique_by(|id| id.as_str().to_owned())
+        .unique_by(|(id, _)| id.as_str().to_owned())
         .rev()
         .collect_vec())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::targets;
+
+    fn plugin(ident: &str, reason: InstallReason, dependencies: &[&str]) -> Plugin {
+        Plugin::from_moved_ident(ident.parse().unwrap())
+            .with_reason(reason)
+            .with_dependencies(dependencies.iter().map(|x| x.parse().unwrap()).collect())
+    }
+
+    #[test]
+    fn orphaned_plugins_cascade() {
+        let collection = Collection {
+            name: "EXAMPLE".to_owned(),
+            id: uuid::Uuid::new_v4(),
+            game: targets::from_slug("valheim").unwrap(),
+            launch_options: Default::default(),
+            plugins: vec![
+                plugin("denikson-BepInExPack_Valheim-5.4.2202", InstallReason::Dependency, &[]),
+                plugin("ValheimModding-Jotunn-2.25.0", InstallReason::Dependency, &["ValheimModding-HookGenPatcher"]),
+                plugin("ValheimModding-HookGenPatcher-0.0.4", InstallReason::Dependency, &[]),
+                plugin("Azumatt-AzuCraftyBoxes-1.8.0", InstallReason::Explicit, &["ValheimModding-HookGenPatcher"]),
+                plugin("Example-Unused-1.0.0", InstallReason::Dependency, &[]),
+            ],
+        };
+
+        // Jotunn is no longer required, HookGenPatcher is still required by an explicit plugin.
+        assert_eq!(orphaned_plugins(&collection.plugins, &collection.game.mod_loader), vec![1, 4]);
+    }
+
+    #[test]
+    fn orphaned_plugins_keeps_required_chain() {
+        let collection = Collection {
+            name: "EXAMPLE".to_owned(),
+            id: uuid::Uuid::new_v4(),
+            game: targets::from_slug("valheim").unwrap(),
+            launch_options: Default::default(),
+            plugins: vec![
+                plugin("Example-Root-1.0.0", InstallReason::Explicit, &["Example-Middle"]),
+                plugin("Example-Middle-1.0.0", InstallReason::Dependency, &["Example-Leaf"]),
+                plugin("Example-Leaf-1.0.0", InstallReason::Dependency, &[]),
+            ],
+        };
+
+        assert!(orphaned_plugins(&collection.plugins, &collection.game.mod_loader).is_empty());
+    }
+}
@@ -1,33 +1,344 @@
 use std::{
-    fs::DirEntry,
+    collections::BTreeMap,
     path::{Path, PathBuf},
 };
 
-use crate::targets::{ModLoader, ModLoaderKind};
-use eyre::{ensure, Context, OptionExt, Result};
+use crate::targets::{ModLoaderKind, Target};
+use crate::utils::{fs, paths};
+use eyre::{ensure, OptionExt, Result};
+#[cfg(target_os = "windows")]
+use eyre::Context;
+use serde::{Deserialize, Serialize};
 use tokio::process::Command;
 use crate::collection::Collection;
+use crate::platforms;
 
+/// User provided options applied whenever a [`Collection`] is launched.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchOptions {
+    /// Extra arguments passed to the game, e.g. `-console`.
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// A command the game is run through, e.g. `gamemoderun` or `mangohud`.
+    /// Empty when the game is launched directly.
+    #[serde(default)]
+    pub wrapper: Vec<String>,
+    /// Where the game is installed, used instead of looking it up through the
+    /// target's platforms, e.g. for DRM-free copies. The game is then always
+    /// launched directly.
+    #[serde(default)]
+    pub game_dir: Option<PathBuf>,
+    /// The game's executable relative to [`LaunchOptions::game_dir`], found
+    /// automatically when not set.
+    #[serde(default)]
+    pub executable: Option<PathBuf>,
+}
+
+impl LaunchOptions {
+    /// Checks that the game directory and executable overrides exist.
+    pub fn validate(&self) -> Result<()> {
+        if let Some(game_dir) = &self.game_dir {
+            ensure!(
+                game_dir.is_absolute() && game_dir.is_dir(),
+                "game directory {} does not exist",
+                game_dir.display()
+            );
+        }
+
+        if let Some(executable) = &self.executable {
+            let game_dir = self.game_dir
+                .as_ref()
+                .ok_or_eyre("the executable can only be overridden along with the game directory")?;
+
+            ensure!(
+                game_dir.join(executable).is_file(),
+                "executable {} does not exist in {}",
+                executable.display(),
+                game_dir.display()
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Adds the [`LaunchOptions`] to an otherwise complete launch command. When a
+/// wrapper is set the command becomes an argument of the wrapper.
+///
+/// Through a launcher the environment and wrapper would apply to the launcher
+/// rather than the game, so only the arguments are added. The others have to be
+/// set in the launcher, see [`launcher_launch_option`].
+pub fn apply_launch_options(mut command: Command, options: &LaunchOptions, through_launcher: bool) -> Command {
+    command.args(&options.args);
+
+    if through_launcher {
+        return command;
+    }
+
+    command.envs(&options.env);
+
+    let Some((wrapper, wrapper_args)) = options.wrapper.split_first() else {
+        return command;
+    };
+
+    let inner = command.as_std();
+    let mut wrapped = Command::new(wrapper);
+
+    wrapped
+        .args(wrapper_args)
+        .arg(inner.get_program())
+        .args(inner.get_args())
+        .envs(inner.get_envs().filter_map(|(key, value)| Some((key, value?))));
+
+    if let Some(dir) = inner.get_current_dir() {
+        wrapped.current_dir(dir);
+    }
+
+    wrapped
+}
+
+/// Returns the launch option that applies the environment and wrapper of the
+/// [`LaunchOptions`] when the game is started by a launcher, e.g.
+/// `EXAMPLE=1 mangohud %command%`, or `None` when neither is set.
+pub fn launcher_launch_option(options: &LaunchOptions) -> Option<String> {
+    if options.env.is_empty() && options.wrapper.is_empty() {
+        return None;
+    }
+
+    let quote = |value: &str| match value.contains(' ') {
+        true => format!("\"{}\"", value),
+        false => value.to_owned(),
+    };
+
+    let parts = options.env
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, quote(value)))
+        .chain(options.wrapper.iter().map(|x| quote(x)))
+        .chain(std::iter::once("%command%".to_owned()))
+        .collect::<Vec<_>>();
+
+    Some(parts.join(" "))
+}
+
+/// How the game's executable is run on this system.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameRuntime {
+    Native,
+    /// A Windows build run through Proton or Wine.
+    Proton,
+}
+
+impl GameRuntime {
+    /// Works out the runtime from the game directory, outside of Windows a game
+    /// shipping a Windows executable has to be run through Proton.
+    pub async fn detect(game_dir: &Path) -> Result<Self> {
+        if cfg!(windows) {
+            return Ok(GameRuntime::Native);
+        }
+
+        let mut entries = tokio::fs::read_dir(game_dir).await?;
+
+        while let Some(entry) = entries.next_entry().await? {
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+
+            if file_name.ends_with(".exe") && !file_name.contains("UnityCrashHandler") {
+                return Ok(GameRuntime::Proton);
+            }
+        }
+
+        Ok(GameRuntime::Native)
+    }
+}
+
+/// Sets up the mod loader for the launch, through the command or files in the game
+/// directory. Must be called after [`link_files`].
 pub async fn add_loader_args(
     command: &mut Command,
-    dir: &Path,
-    mod_loader: &ModLoader<'_>,
+    collection: &Collection,
+    game_dir: &Path,
+    runtime: GameRuntime,
 ) -> Result<()> {
-    match mod_loader.kind {
-        ModLoaderKind::BepInEx => add_bepinex_args(command, dir).await,
+    match collection.game.mod_loader.kind {
+        ModLoaderKind::BepInEx => add_bepinex_args(command, collection, game_dir, runtime).await,
     }
 }
 
-async fn add_bepinex_args(command: &mut Command, dir: &Path) -> Result<()> {
-    let entry_dll = bepinex_dll_path(dir).await?;
+async fn add_bepinex_args(
+    command: &mut Command,
+    collection: &Collection,
+    game_dir: &Path,
+    runtime: GameRuntime,
+) -> Result<()> {
+    let collection_dir = paths::collection_dir(&collection.id);
+    let entry_dll = bepinex_dll_path(&collection_dir).await?;
 
-    command
-        .args(["--doorstop-enable", "true", "--doorstop-target"])
-        .arg(entry_dll);
+    if cfg!(unix) && runtime == GameRuntime::Native {
+        let linux = collection.game.linux.as_ref().ok_or_eyre(format!(
+            "{} has no native Linux build that can be launched with BepInEx",
+            collection.game.name
+        ))?;
+
+        add_doorstop_env(command, &collection_dir.join(linux.doorstop_library), &entry_dll);
+
+        return Ok(());
+    }
+
+    // Launchers don't reliably pass arguments on to the game, so Doorstop is
+    // pointed at the preloader through its config file instead.
+    let host_path = |path: &Path| match runtime {
+        // Doorstop runs inside Wine, where host paths are only reachable through a drive.
+        GameRuntime::Proton => platforms::to_wine_path(path),
+        GameRuntime::Native => path.to_string_lossy().into_owned(),
+    };
+
+    let shipped_config = tokio::fs::read_to_string(collection_dir.join(DOORSTOP_CONFIG_FILE_NAME))
+        .await
+        .ok()
+        .filter(|x| DoorstopVersion::from_config(x).is_some());
+
+    let shipped_config = match shipped_config {
+        Some(config) => config,
+        None => DoorstopVersion::detect(&collection_dir, &entry_dll).await.template().to_owned(),
+    };
+
+    let config = render_doorstop_config(&shipped_config, &host_path(&entry_dll), |value| {
+        host_path(&collection_dir.join(value.replace('\\', "/")))
+    });
+
+    write_generated_file(collection.game, game_dir, Path::new(DOORSTOP_CONFIG_FILE_NAME), &config).await?;
+
+    if runtime == GameRuntime::Proton {
+        command.env("WINEDLLOVERRIDES", platforms::WINE_DLL_OVERRIDES);
+    }
 
     Ok(())
 }
 
+const DOORSTOP_CONFIG_FILE_NAME: &str = "doorstop_config.ini";
+
+/// The two config formats used by Doorstop's `winhttp.dll` proxy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DoorstopVersion {
+    /// Used by BepInEx 5 before 5.4.22, with a single `[UnityDoorstop]` section.
+    V3,
+    /// Used by later BepInEx 5 and BepInEx 6 packs, with snake case keys.
+    V4,
+}
+
+impl DoorstopVersion {
+    fn from_config(config: &str) -> Option<Self> {
+        config.lines().find_map(|line| match line.trim() {
+            "[UnityDoorstop]" => Some(DoorstopVersion::V3),
+            "[General]" => Some(DoorstopVersion::V4),
+            _ => None,
+        })
+    }
+
+    /// Works out the version from the `.doorstop_version` file packs ship, falling
+    /// back to the preloader, as only BepInEx 6 has Unity specific preloaders.
+    async fn detect(collection_dir: &Path, entry_dll: &Path) -> Self {
+        if let Ok(version) = tokio::fs::read_to_string(collection_dir.join(".doorstop_version")).await {
+            return match version.trim().starts_with('3') {
+                true => DoorstopVersion::V3,
+                false => DoorstopVersion::V4,
+            };
+        }
+
+        match entry_dll.file_name().is_some_and(|x| x.to_string_lossy().starts_with("BepInEx.Unity.")) {
+            true => DoorstopVersion::V4,
+            false => DoorstopVersion::V3,
+        }
+    }
+
+    fn template(&self) -> &'static str {
+        match self {
+            DoorstopVersion::V3 => "[UnityDoorstop]\n\
+                enabled=true\n\
+                targetAssembly=\n\
+                redirectOutputLog=false\n\
+                ignoreDisableSwitch=false\n\
+                dllSearchPathOverride=\n",
+            DoorstopVersion::V4 => "[General]\n\
+                enabled = true\n\
+                target_assembly = \n\
+                redirect_output_log = false\n\
+                boot_config_override = \n\
+                ignore_disable_switch = false\n\
+                \n\
+                [UnityMono]\n\
+                dll_search_path_override = \n\
+                debug_enabled = false\n\
+                debug_address = 127.0.0.1:10000\n\
+                debug_suspend = false\n\
+                \n\
+                [Il2Cpp]\n\
+                coreclr_path = dotnet\\coreclr.dll\n\
+                corlib_dir = dotnet\n",
+        }
+    }
+}
+
+/// Fills in a Doorstop config of either version, enabling it and pointing it at
+/// the preloader. Paths the config holds relative to the game directory are
+/// passed through `resolve`, as they actually live in the collection directory.
+fn render_doorstop_config(config: &str, target_assembly: &str, resolve: impl Fn(&str) -> String) -> String {
+    let mut rendered = String::new();
+
+    for line in config.lines() {
+        let Some((key, value)) = line.split_once('=').filter(|_| !line.trim_start().starts_with(['#', ';'])) else {
+            rendered.push_str(line);
+            rendered.push('\n');
+
+            continue;
+        };
+
+        let new_value = match key.trim() {
+            "enabled" => "true".to_owned(),
+            "targetAssembly" | "target_assembly" => target_assembly.to_owned(),
+            "dllSearchPathOverride" | "dll_search_path_override" | "coreclr_path" | "corlib_dir"
+                if !value.trim().is_empty() =>
+            {
+                resolve(value.trim())
+            }
+            _ => value.trim().to_owned(),
+        };
+
+        let separator = match value.starts_with(' ') {
+            true => "= ",
+            false => "=",
+        };
+
+        rendered.push_str(key);
+        rendered.push_str(separator);
+        rendered.push_str(&new_value);
+        rendered.push('\n');
+    }
+
+    rendered
+}
+
+/// Native Linux builds load Doorstop by preloading its library, which is configured
+/// through the environment rather than arguments. Both the Doorstop 3 and 4
+/// variables are set as BepInEx packs ship either.
+fn add_doorstop_env(command: &mut Command, library: &Path, entry_dll: &Path) {
+    let mut preload = library.as_os_str().to_owned();
+
+    if let Some(existing) = std::env::var_os("LD_PRELOAD").filter(|x| !x.is_empty()) {
+        preload.push(":");
+        preload.push(existing);
+    }
+
+    command
+        .env("LD_PRELOAD", preload)
+        .env("DOORSTOP_ENABLED", "1")
+        .env("DOORSTOP_TARGET_ASSEMBLY", entry_dll)
+        .env("DOORSTOP_ENABLE", "TRUE")
+        .env("DOORSTOP_INVOKE_DLL_PATH", entry_dll);
+}
+
 async fn bepinex_dll_path(dir: &Path) -> Result<PathBuf> {
     let core_dir = dir.join("BepInEx").join("core");
 
@@ -44,8 +355,8 @@ async fn bepinex_dll_path(dir: &Path) -> Result<PathBuf> {
             .filter_map(|x| x.ok())
             .find(|x| {
                 DLL_NAMES
-                    .into_iter()
-                    .any(|name| x.file_name().to_str() == Some(name))
+                    .iter()
+                    .any(|name| x.file_name().to_str() == Some(*name))
             })
             .ok_or_eyre("failed to find bepinex entry dll")?
             .path();
@@ -57,11 +368,34 @@ async fn bepinex_dll_path(dir: &Path) -> Result<PathBuf> {
     Ok(dll_path)
 }
 
-/// Returns the path to the game's executable.
-pub async fn app_path(app_dir: &Path) -> Result<PathBuf> {
+/// Returns the path to the game's executable, which is either the [`Collection`]'s
+/// override or, outside of Windows, the target's native Linux executable when it
+/// has one. Games run through Proton use their Windows executable.
+pub async fn app_path(collection: &Collection, app_dir: &Path, runtime: GameRuntime) -> Result<PathBuf> {
+    let target = collection.game;
+
+    if let Some(executable) = &collection.launch_options.executable {
+        return Ok(app_dir.join(executable));
+    }
+
+    if cfg!(unix)
+        && runtime == GameRuntime::Native
+        && let Some(linux) = &target.linux
+    {
+        let executable = app_dir.join(linux.executable);
+
+        ensure!(executable.exists(), "failed to find the game's executable {}", executable.display());
+
+        return Ok(executable);
+    }
+
     let app_dir = app_dir.to_path_buf();
+    let wanted_ext = match cfg!(windows) || runtime == GameRuntime::Proton {
+        true => "exe",
+        false => "sh",
+    };
 
-    Ok(tokio::task::spawn_blocking(move || -> Result<PathBuf> {
+    tokio::task::spawn_blocking(move || -> Result<PathBuf> {
         app_dir
             .read_dir()?
             .filter_map(Result::ok)
@@ -69,64 +403,235 @@ pub async fn app_path(app_dir: &Path) -> Result<PathBuf> {
                 let file_name = PathBuf::from(x.file_name());
                 let ext = file_name.extension().and_then(|ext| ext.to_str());
 
-                let correct_ext = if cfg!(windows) {
-                    matches!(ext, Some("exe"))
-                } else {
-                    matches!(ext, Some("sh"))
-                };
-
-                correct_ext
+                ext == Some(wanted_ext)
                     && !file_name.to_string_lossy().contains("UnityCrashHandler")
                     && !file_name.to_string_lossy().contains("server")
             })
             .map(|entry| entry.path())
             .ok_or_eyre("failed to find the game's executable")
     })
-    .await??)
+    .await?
+}
+
+/// Records the files [`link_files`] placed in a game directory, so that it can be
+/// returned to its original state.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkRecord {
+    pub game_dir: PathBuf,
+    /// Files placed in the game directory, relative to it.
+    pub files: Vec<PathBuf>,
+    /// Game files that were replaced, relative to the game directory. The
+    /// originals are kept in the target's backup directory.
+    pub backups: Vec<PathBuf>,
+    /// Directories created in the game directory, relative to it.
+    #[serde(default)]
+    pub dirs: Vec<PathBuf>,
+}
+
+impl LinkRecord {
+    async fn load(target: Target) -> Result<Option<Self>> {
+        let record_path = paths::link_record_path(&target.slug);
+
+        if !record_path.exists() {
+            return Ok(None);
+        }
+
+        Ok(Some(serde_json::from_str(&tokio::fs::read_to_string(record_path).await?)?))
+    }
+
+    async fn save(&self, target: Target) -> Result<()> {
+        let record_path = paths::link_record_path(&target.slug);
+
+        tokio::fs::create_dir_all(record_path.parent().unwrap()).await?;
+        tokio::fs::write(record_path, serde_json::to_string_pretty(self)?).await?;
+
+        Ok(())
+    }
 }
 
 /// Copies the files to the game directory that are required
 /// for launch. Such as `winhttp.dll` for `BepInEx`.
-pub async fn link_files(collection_dir: &Path, game_dir: &Path) -> Result<()> {
-    let collection_dir = collection_dir.to_path_buf();
+///
+/// Files left by a previous launch are removed first and any game file that
+/// gets replaced is backed up, see [`unlink_game_dir`].
+pub async fn link_files(collection: &Collection, game_dir: &Path) -> Result<()> {
+    unlink_game_dir(collection.game).await?;
 
-    let files = tokio::task::spawn_blocking(move || -> Result<Vec<DirEntry>> {
-        let files = collection_dir
-            .read_dir()?
-            .filter_map(|x| x.ok())
-            .filter(|entry| {
-                entry.file_type().is_ok_and(|ft| ft.is_file()) || entry.file_name() == "dotnet"
-            })
-            .collect();
+    let collection_dir = paths::collection_dir(&collection.id);
+    let backup_dir = paths::game_backup_dir(&collection.game.slug);
 
-        Ok(files)
-    })
-    .await??;
+    let mut record = LinkRecord {
+        game_dir: game_dir.to_path_buf(),
+        ..Default::default()
+    };
 
-    for file in files {
-        tracing::info!(
-            "linking file {} to game dir",
-            file.file_name().to_string_lossy()
-        );
+    for rel_path in files_to_link(&collection_dir, collection.game.mod_loader.linked_dirs()).await? {
+        tracing::info!("linking file {} to game dir", rel_path.display());
+
+        let target_path = game_dir.join(&rel_path);
+
+        // Record each directory we create, outermost first, so they can be removed.
+        for ancestor in rel_path.ancestors().skip(1).collect::<Vec<_>>().into_iter().rev() {
+            if !ancestor.as_os_str().is_empty() && !game_dir.join(ancestor).exists() {
+                tokio::fs::create_dir(game_dir.join(ancestor)).await?;
+
+                record.dirs.push(ancestor.to_path_buf());
+            }
+        }
+
+        if target_path.exists() {
+            if is_same_file(&collection_dir.join(&rel_path), &target_path).await? {
+                tokio::fs::remove_file(&target_path).await?;
+            }
+            else {
+                move_file(&target_path, &backup_dir.join(&rel_path)).await?;
+
+                record.backups.push(rel_path.clone());
+            }
+        }
+
+        let src_path = collection_dir.join(&rel_path);
+
+        if let Err(err) = tokio::fs::hard_link(&src_path, &target_path).await {
+            tracing::info!("hard linking {} failed, copying instead: {}", rel_path.display(), err);
+
+            tokio::fs::copy(&src_path, &target_path).await?;
+        }
+
+        record.files.push(rel_path);
+    }
+
+    record.save(collection.game).await
+}
 
-        if file.file_type().is_ok_and(|f| f.is_file()) {
-            tokio::fs::copy(file.path(), game_dir.join(file.file_name())).await?;
+/// Returns the collection's top-level files along with every file within the
+/// linked directories, relative to the collection directory.
+async fn files_to_link(collection_dir: &Path, linked_dirs: &[&str]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut entries = tokio::fs::read_dir(collection_dir).await?;
+
+    while let Some(entry) = entries.next_entry().await? {
+        let file_type = entry.file_type().await?;
+        let file_name = entry.file_name();
+
+        if file_type.is_file() {
+            files.push(PathBuf::from(file_name));
+        }
+        else if file_type.is_dir() && linked_dirs.iter().any(|x| file_name == *x) {
+            for dir_entry in fs::iterate_directory(entry.path()).await? {
+                if dir_entry.file_type().await?.is_file() {
+                    files.push(dir_entry.path().strip_prefix(collection_dir)?.to_path_buf());
+                }
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// Removes every file [`link_files`] placed in the target's game directory and
+/// restores the game files it replaced. Returns whether anything was linked.
+pub async fn unlink_game_dir(target: Target) -> Result<bool> {
+    let Some(record) = LinkRecord::load(target).await? else {
+        return Ok(false);
+    };
+
+    let backup_dir = paths::game_backup_dir(&target.slug);
+
+    for rel_path in &record.files {
+        let path = record.game_dir.join(rel_path);
+
+        if path.exists() {
+            tracing::info!("removing linked file {} from game dir", rel_path.display());
+
+            tokio::fs::remove_file(path).await?;
         }
+    }
+
+    // The game may have been uninstalled since, in which case there is nothing to restore.
+    for rel_path in record.backups.iter().filter(|_| record.game_dir.exists()) {
+        move_file(&backup_dir.join(rel_path), &record.game_dir.join(rel_path)).await?;
+    }
+
+    // Innermost first, anything the game has since written to them is kept.
+    for rel_path in record.dirs.iter().rev() {
+        let path = record.game_dir.join(rel_path);
+
+        if path.exists()
+            && let Err(err) = tokio::fs::remove_dir(&path).await
+        {
+            tracing::warn!("leaving linked dir {} in game dir: {}", rel_path.display(), err);
+        }
+    }
+
+    if backup_dir.exists() {
+        tokio::fs::remove_dir_all(backup_dir).await?;
+    }
+
+    tokio::fs::remove_file(paths::link_record_path(&target.slug)).await?;
+
+    Ok(true)
+}
+
+/// Writes a file generated for the launch into the game directory, recording it
+/// like the files placed by [`link_files`] so it is removed the same way.
+async fn write_generated_file(target: Target, game_dir: &Path, rel_path: &Path, contents: &str) -> Result<()> {
+    let mut record = LinkRecord::load(target).await?.unwrap_or_else(|| LinkRecord {
+        game_dir: game_dir.to_path_buf(),
+        ..Default::default()
+    });
+
+    let path = game_dir.join(rel_path);
+
+    if !record.files.iter().any(|x| x == rel_path) {
+        if path.exists() {
+            move_file(&path, &paths::game_backup_dir(&target.slug).join(rel_path)).await?;
+
+            record.backups.push(rel_path.to_path_buf());
+        }
+
+        record.files.push(rel_path.to_path_buf());
+    }
 
-        // TODO: Copy directories here too...
+    // Linked files share their contents with the collection, so the link is
+    // removed rather than written through.
+    if path.exists() {
+        tokio::fs::remove_file(&path).await?;
+    }
+
+    tokio::fs::write(&path, contents).await?;
+
+    record.save(target).await
+}
+
+/// Moves a file, copying it when the destination is on another file system.
+async fn move_file(src: &Path, dest: &Path) -> Result<()> {
+    tokio::fs::create_dir_all(dest.parent().unwrap()).await?;
+
+    if tokio::fs::rename(src, dest).await.is_err() {
+        tokio::fs::copy(src, dest).await?;
+        tokio::fs::remove_file(src).await?;
     }
 
     Ok(())
 }
 
-pub async fn create_link(collection: &Collection) -> Result<()> {
-    let desktop_dir = dirs_next::desktop_dir()
-        .ok_or_eyre("failed to get desktop directory")?;
+/// Whether both files have the same contents, used to tell a file left by an
+/// earlier, unrecorded launch apart from one that belongs to the game.
+async fn is_same_file(a: &Path, b: &Path) -> Result<bool> {
+    let (a, b) = (tokio::fs::read(a).await?, tokio::fs::read(b).await?);
+
+    Ok(a == b)
+}
 
+/// Creates a desktop shortcut that launches the provided [`Collection`]. On Linux
+/// the shortcut is also added to the applications menu.
+#[cfg_attr(not(any(target_os = "windows", target_os = "linux")), allow(unused_variables))]
+pub async fn create_link(collection: &Collection) -> Result<()> {
     #[cfg(target_os = "windows")]
     {
-        let shortcut_path =
-            desktop_dir.join(format!("KB - {} - {}.lnk", collection.game.name, collection.name));
+        let shortcut_path = link_path(collection)?;
 
         let exe_path = std::env::current_exe()?;
 
@@ -159,5 +664,261 @@ pub async fn create_link(collection: &Collection) -> Result<()> {
         );
     }
 
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let icon = collection.game.platforms.steam
+            .as_ref()
+            .and_then(|steam| platforms::steam_artwork(steam.id, platforms::SteamArtwork::Icon))
+            .map_or("applications-games".to_owned(), |x| x.to_string_lossy().into_owned());
+
+        let entry = desktop_entry(collection, &std::env::current_exe()?, &icon);
+
+        for shortcut_path in link_paths(collection)? {
+            tokio::fs::create_dir_all(shortcut_path.parent().unwrap()).await?;
+            tokio::fs::write(&shortcut_path, &entry).await?;
+
+            // Desktop environments only run entries on the desktop that are executable.
+            tokio::fs::set_permissions(&shortcut_path, std::fs::Permissions::from_mode(0o755)).await?;
+        }
+    }
+
     Ok(())
 }
+
+/// Removes the desktop shortcut for the provided [`Collection`] if there is one,
+/// returns whether a shortcut was removed.
+#[cfg_attr(not(any(target_os = "windows", target_os = "linux")), allow(unused_variables, unused_mut))]
+pub async fn remove_link(collection: &Collection) -> Result<bool> {
+    let mut removed = false;
+
+    #[cfg(target_os = "windows")]
+    {
+        let shortcut_path = link_path(collection)?;
+
+        if shortcut_path.exists() {
+            tokio::fs::remove_file(&shortcut_path).await?;
+
+            removed = true;
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    for shortcut_path in link_paths(collection)? {
+        if shortcut_path.exists() {
+            tokio::fs::remove_file(&shortcut_path).await?;
+
+            removed = true;
+        }
+    }
+
+    Ok(removed)
+}
+
+#[cfg(target_os = "windows")]
+fn link_path(collection: &Collection) -> Result<PathBuf> {
+    let desktop_dir = dirs_next::desktop_dir()
+        .ok_or_eyre("failed to get desktop directory")?;
+
+    Ok(desktop_dir.join(format!("KB - {} - {}.lnk", collection.game.name, collection.name)))
+}
+
+/// Returns where the collection's desktop entries go, on the desktop and in the
+/// applications menu. Entries are named by id so renaming doesn't lose track of them.
+#[cfg(target_os = "linux")]
+fn link_paths(collection: &Collection) -> Result<Vec<PathBuf>> {
+    let file_name = format!("katabasis-{}.desktop", collection.id);
+
+    let applications_dir = dirs_next::data_dir()
+        .ok_or_eyre("failed to get data directory")?
+        .join("applications");
+
+    Ok(dirs_next::desktop_dir()
+        .into_iter()
+        .chain([applications_dir])
+        .map(|x| x.join(&file_name))
+        .collect())
+}
+
+/// Builds a freedesktop desktop entry that runs `<exe> launch <name>`.
+#[cfg(target_os = "linux")]
+fn desktop_entry(collection: &Collection, exe_path: &Path, icon: &str) -> String {
+    // Arguments are quoted, then the whole value is escaped again as a string.
+    let quote = |arg: &str| {
+        let arg = ['\\', '"', '`', '$']
+            .iter()
+            .fold(arg.to_owned(), |arg, x| arg.replace(*x, &format!("\\{}", x)));
+
+        format!("\"{}\"", arg.replace('\\', "\\\\").replace('%', "%%"))
+    };
+
+    let escape = |value: &str| value.replace('\\', "\\\\").replace('\n', "\\n");
+
+    format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name={}\n\
+         Comment={}\n\
+         Exec={} launch {}\n\
+         Icon={}\n\
+         Terminal=false\n\
+         Categories=Game;\n",
+        escape(&format!("KB - {} - {}", collection.game.name, collection.name)),
+        escape(&format!("Play {} with the {} collection", collection.game.name, collection.name)),
+        quote(&exe_path.to_string_lossy()),
+        quote(&collection.name),
+        escape(icon),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrap_launch_command() {
+        let mut command = Command::new("game.sh");
+        command.arg("--doorstop-enable").arg("true");
+
+        let options = LaunchOptions {
+            args: vec!["-console".to_owned()],
+            env: BTreeMap::from([("EXAMPLE".to_owned(), "1".to_owned())]),
+            wrapper: vec!["mangohud".to_owned(), "--dlsym".to_owned()],
+            ..Default::default()
+        };
+
+        let command = apply_launch_options(command, &options, false);
+        let command = command.as_std();
+
+        assert_eq!(command.get_program(), "mangohud");
+        assert_eq!(
+            command.get_args().collect::<Vec<_>>(),
+            ["--dlsym", "game.sh", "--doorstop-enable", "true", "-console"]
+        );
+        assert_eq!(
+            command.get_envs().collect::<Vec<_>>(),
+            [(std::ffi::OsStr::new("EXAMPLE"), Some(std::ffi::OsStr::new("1")))]
+        );
+    }
+
+    #[test]
+    fn launcher_launch_options() {
+        let mut command = Command::new("xdg-open");
+        command.arg("steam://rungameid/892970");
+
+        let mut options = LaunchOptions {
+            args: vec!["-console".to_owned()],
+            env: BTreeMap::from([("EXAMPLE".to_owned(), "a b".to_owned())]),
+            wrapper: vec!["mangohud".to_owned()],
+            ..Default::default()
+        };
+
+        let command = apply_launch_options(command, &options, true);
+        let command = command.as_std();
+
+        assert_eq!(command.get_program(), "xdg-open");
+        assert_eq!(command.get_args().collect::<Vec<_>>(), ["steam://rungameid/892970", "-console"]);
+        assert_eq!(command.get_envs().count(), 0);
+
+        assert_eq!(launcher_launch_option(&options).as_deref(), Some("EXAMPLE=\"a b\" mangohud %command%"));
+
+        options.env.clear();
+        options.wrapper.clear();
+
+        assert_eq!(launcher_launch_option(&options), None);
+    }
+
+    #[test]
+    fn validate_game_dir_override() {
+        let game_dir = std::env::temp_dir().join(format!("katabasis-game-{}", uuid::Uuid::new_v4()));
+
+        std::fs::create_dir_all(&game_dir).unwrap();
+        std::fs::write(game_dir.join("game.exe"), []).unwrap();
+
+        let mut options = LaunchOptions {
+            game_dir: Some(game_dir.clone()),
+            executable: Some(PathBuf::from("game.exe")),
+            ..Default::default()
+        };
+
+        assert!(options.validate().is_ok());
+
+        options.executable = Some(PathBuf::from("missing.exe"));
+        assert!(options.validate().is_err());
+
+        options.game_dir = None;
+        assert!(options.validate().is_err());
+
+        std::fs::remove_dir_all(game_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn find_proton_executable() {
+        let game_dir = std::env::temp_dir().join(format!("katabasis-game-{}", uuid::Uuid::new_v4()));
+
+        std::fs::create_dir_all(&game_dir).unwrap();
+        std::fs::write(game_dir.join("UnityCrashHandler64.exe"), []).unwrap();
+        std::fs::write(game_dir.join("valheim.exe"), []).unwrap();
+
+        let collection = Collection {
+            name: "EXAMPLE".to_owned(),
+            id: uuid::Uuid::new_v4(),
+            game: crate::targets::from_slug("valheim").unwrap(),
+            plugins: Vec::new(),
+            launch_options: Default::default(),
+        };
+
+        assert_eq!(
+            app_path(&collection, &game_dir, GameRuntime::Proton).await.unwrap(),
+            game_dir.join("valheim.exe")
+        );
+
+        std::fs::remove_dir_all(game_dir).unwrap();
+    }
+
+    #[test]
+    fn render_doorstop_configs() {
+        let resolve = |value: &str| format!("C:\\Collection\\{}", value);
+
+        let shipped = "[UnityDoorstop]\n\
+            # Whether to load the preloader\n\
+            enabled=false\n\
+            targetAssembly=BepInEx\\core\\BepInEx.Preloader.dll\n\
+            dllSearchPathOverride=unstripped_corlib\n";
+
+        assert_eq!(
+            render_doorstop_config(shipped, "C:\\Preloader.dll", resolve),
+            "[UnityDoorstop]\n\
+            # Whether to load the preloader\n\
+            enabled=true\n\
+            targetAssembly=C:\\Preloader.dll\n\
+            dllSearchPathOverride=C:\\Collection\\unstripped_corlib\n"
+        );
+
+        let rendered = render_doorstop_config(DoorstopVersion::V4.template(), "C:\\Preloader.dll", resolve);
+
+        assert_eq!(DoorstopVersion::from_config(&rendered), Some(DoorstopVersion::V4));
+        assert!(rendered.contains("target_assembly = C:\\Preloader.dll\n"));
+        assert!(rendered.contains("dll_search_path_override = \n"));
+        assert!(rendered.contains("corlib_dir = C:\\Collection\\dotnet\n"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn quote_desktop_entry_exec() {
+        let collection = Collection {
+            name: "100% \"Modded\"".to_owned(),
+            id: uuid::Uuid::new_v4(),
+            game: crate::targets::from_slug("valheim").unwrap(),
+            plugins: Vec::new(),
+            launch_options: Default::default(),
+        };
+
+        let entry = desktop_entry(&collection, Path::new("/opt/katabasis/katabasis"), "applications-games");
+
+        assert!(entry.starts_with("[Desktop Entry]\n"));
+        assert!(entry.contains("Name=KB - Valheim - 100% \"Modded\"\n"));
+        assert!(entry.contains("Exec=\"/opt/katabasis/katabasis\" launch \"100%% \\\\\"Modded\\\\\"\"\n"));
+    }
+}
@@ -0,0 +1,204 @@
+use std::path::Path;
+use eyre::{ensure, OptionExt, Result};
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use crate::collection::export::ExportCollection;
+use crate::collection::install::{self, downloader};
+use crate::collection::{Collection, InstallReason, Plugin};
+use crate::{targets, thunderstore};
+use crate::thunderstore::version::{PackageIdent, VersionIdent};
+
+/// An [`ExportCollection`] that also pins where every plugin was downloaded from and
+/// the hash of its archive, so the exact same collection can be rebuilt elsewhere.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Lockfile {
+    #[serde(flatten)]
+    pub collection: ExportCollection,
+    pub locked: Vec<LockedPlugin>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockedPlugin {
+    pub ident: VersionIdent,
+    pub download_url: String,
+    pub sha256: String,
+    pub reason: InstallReason,
+    pub enabled: bool,
+    #[serde(default)]
+    pub dependencies: Vec<PackageIdent>,
+}
+
+impl Lockfile {
+    /// Builds the lockfile for the [`Collection`], this can require downloading
+    /// plugins whose archive hash was not recorded when they were cached.
+    pub async fn from_collection(collection: &Collection) -> Result<Self> {
+        let mut locked = Vec::with_capacity(collection.plugins.len());
+
+        for plugin in &collection.plugins {
+            let package_version = thunderstore::query_specific_package(plugin.ident()).await?;
+            let sha256 = downloader::archive_hash(collection, plugin.ident()).await?;
+
+            locked.push(LockedPlugin {
+                ident: plugin.ident().clone(),
+                download_url: package_version.download_url,
+                sha256,
+                reason: plugin.reason,
+                enabled: plugin.enabled,
+                dependencies: plugin.dependencies.clone(),
+            });
+        }
+
+        Ok(Self {
+            collection: ExportCollection::from_collection(collection),
+            locked,
+        })
+    }
+
+    pub async fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+
+        ensure!(
+            path.exists(),
+            "failed to read lockfile as file does not exist '{}'",
+            path.display()
+        );
+
+        let file_content = tokio::fs::read(path).await?;
+
+        Ok(serde_json::from_slice(file_content.as_slice())?)
+    }
+
+    pub async fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut file = tokio::fs::File::options()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)
+            .await?;
+
+        let json = serde_json::to_vec_pretty(self)?;
+        file.write_all(json.as_slice()).await?;
+
+        Ok(())
+    }
+
+    /// Makes sure every locked plugin is in the cache and matches its locked hash,
+    /// downloading any that aren't cached yet. Fails on the first mismatch.
+    async fn verify(&self) -> Result<()> {
+        let target = targets::from_slug(&self.collection.slug)
+            .ok_or_eyre(format!("lockfile is for unknown target '{}'", self.collection.slug))?;
+
+        for locked in &self.locked {
+            downloader::cache_verified(target, &locked.ident, &locked.download_url, &locked.sha256).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Brings the [`Collection`] to exactly the state in the lockfile. Every archive
+    /// is verified against its locked hash before the collection is changed, any
+    /// mismatch fails the sync. Does not save the collection.
+    pub async fn sync(&self, collection: &mut Collection) -> Result<()> {
+        ensure!(
+            collection.game.slug == self.collection.slug,
+            "lockfile is for target '{}' but collection '{}' is for '{}'",
+            self.collection.slug,
+            collection.name,
+            collection.game.slug
+        );
+
+        self.verify().await?;
+
+        let mut removed = Vec::new();
+
+        for index in (0..collection.plugins.len()).rev() {
+            let ident = collection.plugins[index].ident();
+
+            if !self.locked.iter().any(|x| &x.ident == ident) {
+                removed.push(install::uninstall_plugin(collection, index).await?);
+            }
+        }
+
+        let missing_plugins = self.locked
+            .iter()
+            .filter(|locked| !collection.plugins.iter().any(|x| x.ident() == &locked.ident))
+            .map(|locked| Plugin::from_ident(&locked.ident))
+            .collect::<Vec<_>>();
+
+        downloader::install_plugins(collection, &missing_plugins).await?;
+
+        collection.plugins.extend(missing_plugins);
+
+        let mut plugins = Vec::with_capacity(self.locked.len());
+
+        // Rebuild the plugin list in lockfile order, switching any plugin whose state differs.
+        for locked in &self.locked {
+            let index = collection.plugins
+                .iter()
+                .position(|x| x.ident() == &locked.ident)
+                .expect("all locked plugins have been installed");
+
+            let mut plugin = collection.plugins.remove(index);
+
+            if plugin.enabled != locked.enabled {
+                let installer = collection.game
+                    .mod_loader
+                    .installer_for_plugin(locked.ident.full_name());
+
+                installer.switch(locked.enabled, &plugin, collection).await?;
+            }
+
+            plugin.enabled = locked.enabled;
+            plugin.reason = locked.reason;
+            plugin.dependencies = locked.dependencies.clone();
+
+            plugins.push(plugin);
+        }
+
+        collection.plugins = plugins;
+
+        install::merge_replaced_defaults(collection, &removed).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCKFILE: &str = r#"{
+        "name": "EXAMPLE",
+        "slug": "valheim",
+        "plugins": ["denikson-BepInExPack_Valheim-5.4.2202"],
+        "locked": [
+            {
+                "ident": "denikson-BepInExPack_Valheim-5.4.2202",
+                "downloadUrl": "https://thunderstore.io/package/download/denikson/BepInExPack_Valheim/5.4.2202/",
+                "sha256": "f2ff8f50aa2da1ae78a856e2c4d0768f6f3cf614202dcda7821be09a1ad9caae",
+                "reason": "dependency",
+                "enabled": true
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn parse_lockfile() {
+        let lockfile: Lockfile = serde_json::from_str(LOCKFILE).unwrap();
+
+        assert_eq!(lockfile.collection.name, "EXAMPLE");
+        assert_eq!(lockfile.locked.len(), 1);
+        assert_eq!(lockfile.locked[0].reason, InstallReason::Dependency);
+        assert!(lockfile.locked[0].dependencies.is_empty());
+    }
+
+    #[test]
+    fn lockfile_is_a_valid_export() {
+        let export: ExportCollection = serde_json::from_str(LOCKFILE).unwrap();
+
+        assert_eq!(export.slug, "valheim");
+        assert_eq!(export.plugins[0].as_str(), "denikson-BepInExPack_Valheim-5.4.2202");
+    }
+}
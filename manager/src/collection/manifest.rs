@@ -0,0 +1,504 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Component, Path};
+use eyre::{bail, ensure, eyre, Result};
+use serde::{Deserialize, Serialize};
+use crate::collection::{config, install, Collection, InstallReason, Plugin};
+use crate::targets::{self, ModLoader, Target};
+use crate::thunderstore::version::{PackageIdent, VersionIdent};
+
+/// A declarative description of a collection, read from a TOML or JSON file.
+///
+/// ```toml
+/// name = "Friday Night"
+/// target = "valheim"
+/// disabled = ["Azumatt-AzuCraftyBoxes"]
+///
+/// [[plugins]]
+/// id = "ValheimModding-Jotunn"
+/// version = "2.25.0"
+///
+/// [config."BepInEx.cfg"."Logging.Console"]
+/// Enabled = true
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Manifest {
+    pub name: String,
+    pub target: String,
+    #[serde(default)]
+    pub plugins: Vec<ManifestPlugin>,
+    #[serde(default)]
+    pub disabled: Vec<PackageIdent>,
+    /// Config overrides, keyed by file (relative to the config directory), then
+    /// section and then entry key.
+    #[serde(default)]
+    pub config: BTreeMap<String, BTreeMap<String, BTreeMap<String, ConfigValue>>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ManifestPlugin {
+    pub id: PackageIdent,
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum ConfigValue {
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+}
+
+impl fmt::Display for ConfigValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigValue::Bool(value) => write!(f, "{}", value),
+            ConfigValue::Integer(value) => write!(f, "{}", value),
+            ConfigValue::Float(value) => write!(f, "{}", value),
+            ConfigValue::String(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// The changes required to bring a collection in line with a [`Manifest`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestPlan {
+    pub collection: String,
+    pub create: bool,
+    pub actions: Vec<PlanAction>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum PlanAction {
+    Install { package: PackageIdent, version: Option<String> },
+    Update { from: VersionIdent, version: String },
+    Remove { ident: VersionIdent },
+    Enable { package: PackageIdent },
+    Disable { package: PackageIdent },
+    SetConfig { file: String, section: String, key: String, value: String },
+}
+
+impl ManifestPlan {
+    pub fn is_empty(&self) -> bool {
+        !self.create && self.actions.is_empty()
+    }
+}
+
+impl fmt::Display for ManifestPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "Collection '{}' already matches the manifest", self.collection);
+        }
+
+        if self.create {
+            writeln!(f, "create collection '{}'", self.collection)?;
+        }
+
+        for action in &self.actions {
+            writeln!(f, "{}", action)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for PlanAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanAction::Install { package, version: Some(version) } => {
+                write!(f, "install {}-{}", package.as_str(), version)
+            }
+            PlanAction::Install { package, version: None } => write!(f, "install {}", package.as_str()),
+            PlanAction::Update { from, version } => write!(f, "update {} to {}", from.as_str(), version),
+            PlanAction::Remove { ident } => write!(f, "remove {}", ident.as_str()),
+            PlanAction::Enable { package } => write!(f, "enable {}", package.as_str()),
+            PlanAction::Disable { package } => write!(f, "disable {}", package.as_str()),
+            PlanAction::SetConfig { file, section, key, value } => {
+                write!(f, "set {} [{}] {} = {}", file, section, key, value)
+            }
+        }
+    }
+}
+
+impl Manifest {
+    /// Reads a manifest, the format is picked from the file extension.
+    pub async fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+
+        ensure!(
+            path.exists(),
+            "failed to read manifest as file does not exist '{}'",
+            path.display()
+        );
+
+        let file_content = tokio::fs::read_to_string(path).await?;
+
+        let manifest: Self = match path.extension().and_then(|x| x.to_str()) {
+            Some("toml") => toml::from_str(&file_content)?,
+            Some("json") => serde_json::from_str(&file_content)?,
+            _ => bail!("manifest '{}' must be a .toml or .json file", path.display()),
+        };
+
+        for file in manifest.config.keys() {
+            ensure!(
+                Path::new(file).components().all(|x| matches!(x, Component::Normal(_))),
+                "config file '{}' must be a relative path within the config directory",
+                file
+            );
+        }
+
+        Ok(manifest)
+    }
+
+    /// Returns the [`Target`] the manifest is written for.
+    pub fn target(&self) -> Result<Target> {
+        targets::from_slug(&self.target)
+            .ok_or_else(|| eyre!("manifest target '{}' is not supported", self.target))
+    }
+
+    /// Works out the changes needed to make the [`Collection`] match the manifest.
+    /// `None` is treated as a collection that has not been created yet.
+    pub async fn plan(&self, collection: Option<&Collection>) -> Result<ManifestPlan> {
+        let target = self.target()?;
+
+        if let Some(collection) = collection {
+            ensure!(
+                collection.game.slug == target.slug,
+                "collection '{}' targets '{}' but the manifest targets '{}'",
+                collection.name,
+                collection.game.slug,
+                target.slug
+            );
+        }
+
+        let plugins = collection.map(|x| x.plugins.as_slice()).unwrap_or_default();
+        let mut actions = self.plan_plugins(plugins, &target.mod_loader);
+
+        for (file, sections) in &self.config {
+            let contents = match collection {
+                Some(collection) => config::read_file(collection, file).await?,
+                None => String::new(),
+            };
+
+            for (section, entries) in sections {
+                for (key, value) in entries {
+                    let value = value.to_string();
+
+                    check_config_value(&contents, file, section, key, &value)?;
+
+                    if config::get_value(&contents, section, key) != Some(value.as_str()) {
+                        actions.push(PlanAction::SetConfig {
+                            file: file.clone(),
+                            section: section.clone(),
+                            key: key.clone(),
+                            value,
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(ManifestPlan {
+            collection: self.name.clone(),
+            create: collection.is_none(),
+            actions,
+        })
+    }
+
+    fn plan_plugins(&self, plugins: &[Plugin], mod_loader: &ModLoader) -> Vec<PlanAction> {
+        let mut actions = Vec::new();
+
+        let is_listed = |plugin: &Plugin| {
+            self.plugins.iter().any(|x| x.id == plugin.ident().as_package_ident())
+        };
+
+        // Listed plugins are explicit, unlisted explicit plugins are removed along
+        // with any dependencies only they required.
+        let mut remaining = plugins
+            .iter()
+            .filter(|plugin| {
+                is_listed(plugin)
+                    || plugin.reason == InstallReason::Dependency
+                    || mod_loader.is_loader_plugin(plugin.ident().full_name())
+            })
+            .cloned()
+            .map(|plugin| match is_listed(&plugin) {
+                true => plugin.with_reason(InstallReason::Explicit),
+                false => plugin,
+            })
+            .collect::<Vec<_>>();
+
+        for index in install::orphaned_plugins(&remaining, mod_loader).into_iter().rev() {
+            remaining.remove(index);
+        }
+
+        for plugin in plugins {
+            if !remaining.iter().any(|x| x.ident() == plugin.ident()) {
+                actions.push(PlanAction::Remove { ident: plugin.ident().clone() });
+            }
+        }
+
+        for manifest_plugin in &self.plugins {
+            let installed = remaining
+                .iter()
+                .find(|x| x.ident().as_package_ident() == manifest_plugin.id);
+
+            match (installed, &manifest_plugin.version) {
+                (None, version) => actions.push(PlanAction::Install {
+                    package: manifest_plugin.id.clone(),
+                    version: version.clone(),
+                }),
+                (Some(plugin), Some(version)) if plugin.ident().version() != version => {
+                    actions.push(PlanAction::Update {
+                        from: plugin.ident().clone(),
+                        version: version.clone(),
+                    })
+                }
+                _ => {}
+            }
+        }
+
+        for plugin in &remaining {
+            let package = plugin.ident().as_package_ident();
+            let should_enable = !self.disabled.contains(&package);
+
+            match (plugin.enabled, should_enable) {
+                (false, true) => actions.push(PlanAction::Enable { package }),
+                (true, false) => actions.push(PlanAction::Disable { package }),
+                _ => {}
+            }
+        }
+
+        // Plugins installed by this plan start enabled.
+        let disabled_installs = actions
+            .iter()
+            .filter_map(|action| match action {
+                PlanAction::Install { package, .. } if self.disabled.contains(package) => Some(package.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        actions.extend(disabled_installs.into_iter().map(|package| PlanAction::Disable { package }));
+
+        actions
+    }
+}
+
+/// Carries out a [`ManifestPlan`] against the [`Collection`]. Does not save the collection.
+pub async fn apply(plan: &ManifestPlan, manifest: &Manifest, collection: &mut Collection) -> Result<()> {
+    for action in &plan.actions {
+        tracing::info!("applying manifest action: {}", action);
+
+        match action {
+            PlanAction::Remove { ident } => {
+                let index = position_of(collection, |x| x.ident() == ident)?;
+
+                install::uninstall_plugin(collection, index).await?;
+            }
+            PlanAction::Update { from, version } => {
+                let index = position_of(collection, |x| x.ident() == from)?;
+
+                install::update_plugin(collection, index, Some(version)).await?;
+            }
+            PlanAction::Install { package, version } => {
+                install::install_package(collection, package, version.as_deref()).await?;
+            }
+            PlanAction::Enable { package } | PlanAction::Disable { package } => {
+                let enabled = matches!(action, PlanAction::Enable { .. });
+                let index = collection.plugins
+                    .iter()
+                    .position(|x| &x.ident().as_package_ident() == package);
+
+                match index {
+                    Some(index) => install::switch_plugin(collection, index, enabled).await?,
+                    // Disabled packages don't have to be installed, there is nothing to disable.
+                    None if !enabled => tracing::info!("{} is not installed, skipping", package.as_str()),
+                    None => bail!("failed to find plugin '{}' in collection '{}'", package.as_str(), collection.name),
+                }
+            }
+            PlanAction::SetConfig { file, section, key, value } => {
+                let contents = config::read_file(collection, file).await?;
+                let path = config::file_path(collection, file)?;
+
+                if let Some(parent) = path.parent() {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+
+                tokio::fs::write(path, config::set_value(&contents, section, key, value)).await?;
+            }
+        }
+    }
+
+    for plugin in collection.plugins.iter_mut() {
+        if manifest.plugins.iter().any(|x| x.id == plugin.ident().as_package_ident()) {
+            plugin.reason = InstallReason::Explicit;
+        }
+    }
+
+    Ok(())
+}
+
+/// Fails if the entry doesn't exist in the config file or the value doesn't suit it.
+/// Config files are only written once the game has run with the plugin, so values
+/// for files that don't exist yet can't be checked.
+fn check_config_value(contents: &str, file: &str, section: &str, key: &str, value: &str) -> Result<()> {
+    if contents.is_empty() {
+        return Ok(());
+    }
+
+    let Some(entry) = config::parse(contents)
+        .into_iter()
+        .filter(|x| x.name == section)
+        .flat_map(|x| x.entries)
+        .find(|x| x.key == key)
+    else {
+        bail!("config file '{}' has no entry '{}' in section '{}'", file, key, section);
+    };
+
+    entry.parse_value(value)
+        .map_err(|err| err.wrap_err(format!("invalid value for '{}' in config file '{}'", key, file)))?;
+
+    Ok(())
+}
+
+fn position_of<F>(collection: &Collection, predicate: F) -> Result<usize>
+where
+    F: FnMut(&Plugin) -> bool,
+{
+    collection.plugins
+        .iter()
+        .position(predicate)
+        .ok_or_else(|| eyre!("failed to find plugin in collection '{}'", collection.name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    const MANIFEST: &str = r#"
+        name = "EXAMPLE"
+        target = "valheim"
+        disabled = ["Example-Disabled"]
+
+        [[plugins]]
+        id = "ValheimModding-Jotunn"
+        version = "2.26.0"
+
+        [[plugins]]
+        id = "Example-New"
+
+        [[plugins]]
+        id = "Example-Disabled"
+
+        [config."BepInEx.cfg"."Logging.Console"]
+        Enabled = true
+    "#;
+
+    fn plugin(ident: &str, reason: InstallReason, dependencies: &[&str]) -> Plugin {
+        Plugin::from_moved_ident(ident.parse().unwrap())
+            .with_reason(reason)
+            .with_dependencies(dependencies.iter().map(|x| x.parse().unwrap()).collect())
+    }
+
+    #[tokio::test]
+    async fn disable_absent_package() {
+        let manifest: Manifest = toml::from_str(MANIFEST).unwrap();
+
+        let mut collection = Collection {
+            name: "EXAMPLE".to_owned(),
+            id: Uuid::new_v4(),
+            game: targets::from_slug("valheim").unwrap(),
+            launch_options: Default::default(),
+            plugins: vec![],
+        };
+
+        let plan = ManifestPlan {
+            collection: collection.name.clone(),
+            create: false,
+            actions: vec![PlanAction::Disable { package: "Example-Disabled".parse().unwrap() }],
+        };
+
+        apply(&plan, &manifest, &mut collection).await.unwrap();
+
+        assert!(collection.plugins.is_empty());
+    }
+
+    #[test]
+    fn parse_toml_manifest() {
+        let manifest: Manifest = toml::from_str(MANIFEST).unwrap();
+
+        assert_eq!(manifest.plugins.len(), 3);
+        assert_eq!(manifest.plugins[0].version.as_deref(), Some("2.26.0"));
+        assert_eq!(manifest.config["BepInEx.cfg"]["Logging.Console"]["Enabled"].to_string(), "true");
+    }
+
+    #[test]
+    fn plan_plugin_changes() {
+        let manifest: Manifest = toml::from_str(MANIFEST).unwrap();
+        let target = targets::from_slug("valheim").unwrap();
+
+        let plugins = vec![
+            plugin("denikson-BepInExPack_Valheim-5.4.2202", InstallReason::Dependency, &[]),
+            plugin("ValheimModding-Jotunn-2.25.0", InstallReason::Explicit, &["ValheimModding-HookGenPatcher"]),
+            plugin("ValheimModding-HookGenPatcher-0.0.4", InstallReason::Dependency, &[]),
+            plugin("Example-Unlisted-1.0.0", InstallReason::Explicit, &["Example-OnlyUsedByUnlisted"]),
+            plugin("Example-OnlyUsedByUnlisted-1.0.0", InstallReason::Dependency, &[]),
+            plugin("Example-Disabled-1.0.0", InstallReason::Explicit, &[]),
+        ];
+
+        let actions = manifest.plan_plugins(&plugins, &target.mod_loader);
+
+        assert_eq!(actions, vec![
+            PlanAction::Remove { ident: "Example-Unlisted-1.0.0".parse().unwrap() },
+            PlanAction::Remove { ident: "Example-OnlyUsedByUnlisted-1.0.0".parse().unwrap() },
+            PlanAction::Update {
+                from: "ValheimModding-Jotunn-2.25.0".parse().unwrap(),
+                version: "2.26.0".to_owned(),
+            },
+            PlanAction::Install { package: "Example-New".parse().unwrap(), version: None },
+            PlanAction::Disable { package: "Example-Disabled".parse().unwrap() },
+        ]);
+    }
+
+    #[test]
+    fn plan_applied_manifest() {
+        let mut manifest: Manifest = toml::from_str(MANIFEST).unwrap();
+        let target = targets::from_slug("valheim").unwrap();
+
+        // Disabled packages that aren't part of the collection are left alone.
+        manifest.disabled.push("Example-Absent".parse().unwrap());
+
+        let mut disabled = plugin("Example-Disabled-1.0.0", InstallReason::Explicit, &[]);
+        disabled.enabled = false;
+
+        let plugins = vec![
+            plugin("denikson-BepInExPack_Valheim-5.4.2202", InstallReason::Dependency, &[]),
+            plugin("ValheimModding-Jotunn-2.26.0", InstallReason::Explicit, &["ValheimModding-HookGenPatcher"]),
+            plugin("ValheimModding-HookGenPatcher-0.0.4", InstallReason::Dependency, &[]),
+            plugin("Example-New-1.0.0", InstallReason::Explicit, &[]),
+            disabled,
+        ];
+
+        assert_eq!(manifest.plan_plugins(&plugins, &target.mod_loader), vec![]);
+    }
+
+    #[test]
+    fn check_config_values() {
+        let contents = "[General]\n\n# Setting type: Int32\n# Acceptable value range: From 0 to 10\nCount = 1\n";
+
+        assert!(check_config_value(contents, "Example.cfg", "General", "Count", "5").is_ok());
+        assert!(check_config_value(contents, "Example.cfg", "General", "Count", "11").is_err());
+        assert!(check_config_value(contents, "Example.cfg", "General", "Count", "many").is_err());
+        assert!(check_config_value(contents, "Example.cfg", "General", "Missing", "1").is_err());
+        assert!(check_config_value(contents, "Example.cfg", "Other", "Count", "1").is_err());
+
+        // Files that haven't been generated yet can't be checked.
+        assert!(check_config_value("", "Example.cfg", "General", "Missing", "1").is_ok());
+    }
+}
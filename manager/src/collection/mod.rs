@@ -1,53 +1,153 @@
 use chrono::{DateTime, Utc};
+use eyre::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
 
-use crate::{targets::Target, thunderstore::version::VersionIdent};
+use crate::{
+    collection::launch::LaunchOptions,
+    db::Db,
+    targets::Target,
+    thunderstore::version::{PackageIdent, VersionIdent},
+    utils::{fs, paths},
+};
 
 pub mod install;
 pub mod launch;
 pub mod export;
+pub mod lockfile;
+pub mod snapshot;
+pub mod config;
+pub mod manifest;
+pub mod session;
+pub mod diagnose;
+pub mod bisect;
+pub mod steam_shortcut;
 
 #[derive(Debug, sqlx::FromRow)]
 pub struct Collection {
     /// Acts as the primary key for the collection, should be unique.
     pub name: String,
+    /// Stable identifier used for the collection's directory on disk, never
+    /// changes when the collection is renamed.
+    #[sqlx(try_from = "String")]
+    pub id: Uuid,
     pub game: Target,
     #[sqlx(json)]
     pub plugins: Vec<Plugin>,
+    #[sqlx(json)]
+    pub launch_options: LaunchOptions,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Plugin {
     pub enabled: bool,
     pub install_time: DateTime<Utc>,
+    #[serde(default)]
+    pub reason: InstallReason,
+    /// The packages this plugin depends on, used to work out which
+    /// dependency plugins are still required.
+    #[serde(default)]
+    pub dependencies: Vec<PackageIdent>,
     #[serde(flatten)]
     pub kind: PluginType,
 }
 
+/// Why a [`Plugin`] is part of a [`Collection`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InstallReason {
+    /// The user asked for the plugin to be installed.
+    #[default]
+    Explicit,
+    /// The plugin was pulled in to satisfy another plugin's dependencies.
+    Dependency,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "camelCase")]
 pub enum PluginType {
     Thunderstore { ident: VersionIdent, },
 }
 
+/// Moves collection directories that were named after the sanitised collection
+/// name into their id based directory. Collections whose names sanitised to the
+/// same directory each receive their own copy of it.
+pub async fn move_legacy_dirs(db: &Db) -> Result<()> {
+    let mut legacy_dirs: HashMap<_, Vec<Collection>> = HashMap::new();
+
+    for collection in db.load_all_collections().await? {
+        if paths::collection_dir(&collection.id).exists() {
+            continue;
+        }
+
+        let legacy_dir = paths::legacy_collection_dir(&collection.name);
+
+        if legacy_dir.exists() {
+            legacy_dirs.entry(legacy_dir).or_default().push(collection);
+        }
+    }
+
+    for (legacy_dir, mut collections) in legacy_dirs {
+        // Every collection except the last gets a copy, the last takes the original.
+        let last = collections.pop().expect("legacy dir groups are never empty");
+
+        for collection in collections {
+            tracing::warn!(
+                "collection '{}' shared directory '{}' with another collection, copying it to its own directory",
+                collection.name,
+                legacy_dir.display()
+            );
+
+            let dest = paths::collection_dir(&collection.id);
+
+            tokio::fs::create_dir_all(&dest).await?;
+
+            fs::copy_dir_contents_to(&legacy_dir, dest, |_, _| {
+                Ok(fs::CopyFileOpts { should_copy_file: true, should_overwrite_file: true })
+            }).await?;
+        }
+
+        tracing::info!(
+            "moving collection '{}' from '{}' to its id based directory",
+            last.name,
+            legacy_dir.display()
+        );
+
+        tokio::fs::rename(&legacy_dir, paths::collection_dir(&last.id)).await?;
+    }
+
+    Ok(())
+}
+
 impl Plugin {
     pub fn from_ident(ident: &VersionIdent) -> Self {
-        Plugin {
-            enabled: true,
-            install_time: Utc::now(),
-            kind: PluginType::Thunderstore { ident: ident.clone(), },
-        }
+        Self::from_moved_ident(ident.clone())
     }
 
     pub fn from_moved_ident(ident: VersionIdent) -> Self {
         Self {
             enabled: true,
             install_time: Utc::now(),
+            reason: InstallReason::default(),
+            dependencies: vec![],
             kind: PluginType::Thunderstore { ident },
         }
     }
 
+    pub fn with_reason(mut self, reason: InstallReason) -> Self {
+        self.reason = reason;
+
+        self
+    }
+
+    pub fn with_dependencies(mut self, dependencies: Vec<PackageIdent>) -> Self {
+        self.dependencies = dependencies;
+
+        self
+    }
+
     pub fn ident(&self) -> &VersionIdent {
         match self.kind {
             PluginType::Thunderstore { ref ident } => ident,
@@ -85,28 +185,30 @@ pub struct FrontendCollection {
 pub struct FrontendPlugin {
     pub enabled: bool,
     pub install_time: DateTime<Utc>,
+    pub install_reason: InstallReason,
     pub ident: String,
     pub full_name: String,
 }
 
-impl Into<FrontendPlugin> for Plugin {
-    fn into(self) -> FrontendPlugin {
+impl From<Plugin> for FrontendPlugin {
+    fn from(plugin: Plugin) -> Self {
         FrontendPlugin {
-            enabled: self.enabled,
-            install_time: self.install_time,
-            ident: self.ident().as_str().to_string(),
-            full_name: self.ident().full_name().to_string(),
+            enabled: plugin.enabled,
+            install_time: plugin.install_time,
+            install_reason: plugin.reason,
+            ident: plugin.ident().as_str().to_string(),
+            full_name: plugin.ident().full_name().to_string(),
         }
     }
 }
 
-impl Into<FrontendCollection> for Collection {
-    fn into(self) -> FrontendCollection {
+impl From<Collection> for FrontendCollection {
+    fn from(collection: Collection) -> Self {
         FrontendCollection {
-            name: self.name,
-            target: self.game.name.to_owned(),
-            plugins: self.plugins.into_iter().map(Into::into).collect(),
-            mod_loader: self.game.mod_loader.to_string(),
+            name: collection.name,
+            target: collection.game.name.to_owned(),
+            plugins: collection.plugins.into_iter().map(Into::into).collect(),
+            mod_loader: collection.game.mod_loader.to_string(),
         }
     }
 }
@@ -0,0 +1,404 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use chrono::{DateTime, Utc};
+use eyre::{ensure, Result};
+use serde::{Deserialize, Serialize};
+use strum::{EnumIter, IntoEnumIterator};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+use tokio::process::Child;
+use crate::collection::Collection;
+use crate::event::{self, Event};
+use crate::utils::paths;
+
+/// How long to wait for the game to appear after asking a launcher to start it.
+const LAUNCHER_START_TIMEOUT: Duration = Duration::from_secs(120);
+
+const PROCESS_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Processes launchers start games through. They have the game's path on their
+/// command line without being the game itself.
+const LAUNCHER_WRAPPERS: &[&str] = &[
+    "reaper",
+    "steam-launch-wrapper",
+    "pressure-vessel-wrap",
+    "pressure-vessel-adverb",
+    "pv-bwrap",
+    "srt-bwrap",
+    "bwrap",
+    "proton",
+    "lutris-wrapper",
+    "wineserver",
+];
+
+/// Number of sessions to keep logs for, older sessions are removed.
+const MAX_SESSION_LOGS: usize = 20;
+
+const SESSION_FILE_NAME: &str = "session.json";
+
+/// Accumulated play time for a [`Collection`].
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayStats {
+    pub play_time_secs: i64,
+    pub last_played: Option<DateTime<Utc>>,
+}
+
+/// A single supervised run of the game.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameSession {
+    pub collection: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    /// Only known when the game was started directly rather than through a launcher.
+    pub exit_code: Option<i32>,
+}
+
+impl GameSession {
+    pub fn play_time_secs(&self) -> i64 {
+        (self.ended_at - self.started_at).num_seconds()
+    }
+
+    /// The id used for the session's log directory, based on when it started.
+    pub fn id(&self) -> String {
+        self.started_at.format("%Y%m%dT%H%M%S").to_string()
+    }
+}
+
+/// The logs saved after a supervised session.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionLogs {
+    pub id: String,
+    pub session: GameSession,
+    pub files: Vec<SessionLogFile>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, EnumIter)]
+#[serde(rename_all = "camelCase")]
+pub enum SessionLogFile {
+    /// BepInEx's `LogOutput.log`.
+    BepInEx,
+    /// Unity's `Player.log`.
+    Player,
+}
+
+impl SessionLogFile {
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            SessionLogFile::BepInEx => "LogOutput.log",
+            SessionLogFile::Player => "Player.log",
+        }
+    }
+}
+
+/// Waits for the game started by `child` to exit. When `through_launcher` is set
+/// the child is a launcher, such as Steam, that hands off to the real game process
+/// so that process is found within `game_dir` and waited on instead.
+pub async fn supervise(
+    collection: &Collection,
+    game_dir: &Path,
+    mut child: Child,
+    through_launcher: bool,
+) -> Result<GameSession> {
+    // Process start times only have second precision, so anything started within
+    // the same second as the launch counts as started after it.
+    let launched_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+
+    let game_pid = match through_launcher {
+        true => Some(find_game_process(game_dir, launched_at).await?),
+        false => None,
+    };
+
+    let started_at = Utc::now();
+
+    event::emit(Event::GameStarted {
+        collection: collection.name.clone(),
+        pid: game_pid.map(Pid::as_u32).or(child.id()),
+    })?;
+
+    let exit_code = match game_pid {
+        Some(pid) => {
+            wait_for_process(pid).await;
+
+            // Reap the launcher if it is still around, it doesn't matter if it stays open.
+            let _ = child.try_wait();
+
+            None
+        }
+        None => child.wait().await?.code(),
+    };
+
+    let session = GameSession {
+        collection: collection.name.clone(),
+        started_at,
+        ended_at: Utc::now(),
+        exit_code,
+    };
+
+    event::emit(Event::GameExited {
+        collection: collection.name.clone(),
+        exit_code,
+        play_time_secs: session.play_time_secs(),
+    })?;
+
+    Ok(session)
+}
+
+/// Polls the process list until a process running from the game directory appears,
+/// ignoring processes started before `launched_at` and the launcher's own wrappers.
+/// Proton and Wine run games through their own binaries, so the command line is
+/// checked as well as the executable path.
+async fn find_game_process(game_dir: &Path, launched_at: u64) -> Result<Pid> {
+    let mut system = System::new();
+    let started = tokio::time::Instant::now();
+
+    let refresh_kind = ProcessRefreshKind::nothing()
+        .with_exe(UpdateKind::OnlyIfNotSet)
+        .with_cmd(UpdateKind::OnlyIfNotSet);
+
+    loop {
+        system.refresh_processes_specifics(ProcessesToUpdate::All, true, refresh_kind);
+
+        let game_process = system.processes().values().find(|process| {
+            let name = process.exe()
+                .and_then(Path::file_name)
+                .unwrap_or(process.name())
+                .to_string_lossy();
+
+            let in_game_dir = process.exe().is_some_and(|exe| exe.starts_with(game_dir))
+                || process.cmd().iter().any(|arg| Path::new(arg).starts_with(game_dir));
+
+            in_game_dir && process.start_time() >= launched_at && !is_launcher_wrapper(&name)
+        });
+
+        if let Some(process) = game_process {
+            return Ok(process.pid());
+        }
+
+        ensure!(
+            started.elapsed() < LAUNCHER_START_TIMEOUT,
+            "the game did not start within {} seconds of launching it",
+            LAUNCHER_START_TIMEOUT.as_secs()
+        );
+
+        tokio::time::sleep(PROCESS_POLL_INTERVAL).await;
+    }
+}
+
+async fn wait_for_process(pid: Pid) {
+    let mut system = System::new();
+
+    loop {
+        system.refresh_processes_specifics(
+            ProcessesToUpdate::Some(&[pid]),
+            true,
+            ProcessRefreshKind::nothing(),
+        );
+
+        if system.process(pid).is_none() {
+            return;
+        }
+
+        tokio::time::sleep(PROCESS_POLL_INTERVAL).await;
+    }
+}
+
+/// Copies the logs written during the session into the [`Collection`]'s session
+/// log directory, so they aren't lost when the game is next launched. Logs that
+/// weren't written to during the session are skipped.
+pub async fn save_logs(collection: &Collection, session: &GameSession, game_dir: &Path) -> Result<()> {
+    let session_dir = paths::session_logs_dir(&collection.id).join(session.id());
+
+    tokio::fs::create_dir_all(&session_dir).await?;
+    tokio::fs::write(session_dir.join(SESSION_FILE_NAME), serde_json::to_string_pretty(session)?).await?;
+
+    let bepinex_log = paths::collection_dir(&collection.id)
+        .join("BepInEx")
+        .join(SessionLogFile::BepInEx.file_name());
+
+    let candidates = std::iter::once((SessionLogFile::BepInEx, bepinex_log))
+        .chain(player_log_paths(collection, game_dir).await.into_iter().map(|x| (SessionLogFile::Player, x)));
+
+    for (kind, path) in candidates {
+        let Ok(modified) = tokio::fs::metadata(&path).await.and_then(|x| x.modified()) else {
+            continue;
+        };
+
+        if DateTime::<Utc>::from(modified) < session.started_at
+            || session_dir.join(kind.file_name()).exists()
+        {
+            continue;
+        }
+
+        tokio::fs::copy(&path, session_dir.join(kind.file_name())).await?;
+    }
+
+    prune_logs(collection).await
+}
+
+/// Lists the [`Collection`]'s saved session logs, newest first.
+pub async fn list_logs(collection: &Collection) -> Result<Vec<SessionLogs>> {
+    let logs_dir = paths::session_logs_dir(&collection.id);
+
+    if !logs_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut all_logs = Vec::new();
+    let mut entries = tokio::fs::read_dir(&logs_dir).await?;
+
+    while let Some(entry) = entries.next_entry().await? {
+        let Ok(session_json) = tokio::fs::read_to_string(entry.path().join(SESSION_FILE_NAME)).await else {
+            continue;
+        };
+
+        let files = SessionLogFile::iter()
+            .filter(|x| entry.path().join(x.file_name()).exists())
+            .collect();
+
+        all_logs.push(SessionLogs {
+            id: entry.file_name().to_string_lossy().into_owned(),
+            session: serde_json::from_str(&session_json)?,
+            files,
+        });
+    }
+
+    all_logs.sort_by_key(|x| std::cmp::Reverse(x.session.started_at));
+
+    Ok(all_logs)
+}
+
+/// Reads one of the logs saved for a session.
+pub async fn read_log(collection: &Collection, session_id: &str, file: SessionLogFile) -> Result<String> {
+    ensure!(
+        !session_id.is_empty() && session_id.chars().all(|x| x.is_ascii_alphanumeric()),
+        "invalid session id '{}'",
+        session_id
+    );
+
+    let path = paths::session_logs_dir(&collection.id)
+        .join(session_id)
+        .join(file.file_name());
+
+    ensure!(path.exists(), "session '{}' has no {} log", session_id, file.file_name());
+
+    // Logs can contain output that isn't valid UTF-8, such as native crash dumps.
+    Ok(String::from_utf8_lossy(&tokio::fs::read(path).await?).into_owned())
+}
+
+pub async fn remove_all_logs(collection: &Collection) -> Result<()> {
+    let logs_dir = paths::session_logs_dir(&collection.id);
+
+    if logs_dir.exists() {
+        tokio::fs::remove_dir_all(logs_dir).await?;
+    }
+
+    Ok(())
+}
+
+/// Removes the oldest session logs past [`MAX_SESSION_LOGS`].
+async fn prune_logs(collection: &Collection) -> Result<()> {
+    for expired in list_logs(collection).await?.into_iter().skip(MAX_SESSION_LOGS) {
+        tokio::fs::remove_dir_all(paths::session_logs_dir(&collection.id).join(expired.id)).await?;
+    }
+
+    Ok(())
+}
+
+/// Returns the places Unity may have written the game's `Player.log` to, based on
+/// the company and product names in the game's `app.info`.
+async fn player_log_paths(collection: &Collection, game_dir: &Path) -> Vec<PathBuf> {
+    let Some((company, product)) = read_app_info(game_dir).await else {
+        return Vec::new();
+    };
+
+    let mut log_dirs = Vec::new();
+
+    if cfg!(windows) {
+        log_dirs.extend(dirs_next::home_dir().map(|x| x.join("AppData").join("LocalLow")));
+    }
+    else {
+        log_dirs.extend(dirs_next::config_dir().map(|x| x.join("unity3d")));
+
+        // Proton keeps the Windows style path inside the game's prefix, which
+        // sits alongside the library's `common` directory.
+        if let Some(steam) = &collection.game.platforms.steam
+            && let Some(steamapps) = game_dir.parent().and_then(Path::parent)
+        {
+            log_dirs.push(
+                steamapps
+                    .join("compatdata")
+                    .join(steam.id.to_string())
+                    .join("pfx/drive_c/users/steamuser/AppData/LocalLow"),
+            );
+        }
+    }
+
+    log_dirs
+        .into_iter()
+        .map(|x| x.join(&company).join(&product).join(SessionLogFile::Player.file_name()))
+        .collect()
+}
+
+async fn read_app_info(game_dir: &Path) -> Option<(String, String)> {
+    let mut entries = tokio::fs::read_dir(game_dir).await.ok()?;
+
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        if !entry.file_name().to_string_lossy().ends_with("_Data") {
+            continue;
+        }
+
+        if let Ok(contents) = tokio::fs::read_to_string(entry.path().join("app.info")).await {
+            return parse_app_info(&contents);
+        }
+    }
+
+    None
+}
+
+/// Unity's `app.info` holds the company name on the first line and the product
+/// name on the second.
+fn parse_app_info(contents: &str) -> Option<(String, String)> {
+    let mut lines = contents.lines().map(str::trim);
+
+    let company = lines.next().filter(|x| !x.is_empty())?;
+    let product = lines.next().filter(|x| !x.is_empty())?;
+
+    Some((company.to_owned(), product.to_owned()))
+}
+
+fn is_launcher_wrapper(process_name: &str) -> bool {
+    let process_name = process_name.to_lowercase();
+
+    // Proton is a Python script, the interpreter's name includes its version.
+    LAUNCHER_WRAPPERS.contains(&process_name.as_str()) || process_name.starts_with("python")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skip_launcher_wrappers() {
+        assert!(is_launcher_wrapper("reaper"));
+        assert!(is_launcher_wrapper("pressure-vessel-wrap"));
+        assert!(is_launcher_wrapper("python3.12"));
+
+        assert!(!is_launcher_wrapper("valheim.x86_64"));
+        assert!(!is_launcher_wrapper("wine64-preloader"));
+    }
+
+    #[test]
+    fn parse_unity_app_info() {
+        assert_eq!(
+            parse_app_info("IronGate\nValheim"),
+            Some(("IronGate".to_owned(), "Valheim".to_owned()))
+        );
+        assert_eq!(
+            parse_app_info("Zeekerss\r\nLethal Company\r\n"),
+            Some(("Zeekerss".to_owned(), "Lethal Company".to_owned()))
+        );
+        assert_eq!(parse_app_info("OnlyCompany"), None);
+    }
+}
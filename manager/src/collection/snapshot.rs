@@ -0,0 +1,173 @@
+use crate::collection::install::downloader;
+use crate::collection::{Collection, Plugin};
+use crate::db::Db;
+use crate::utils::{fs, paths};
+use chrono::{DateTime, Utc};
+use eyre::{ensure, Result};
+use serde::Serialize;
+use std::path::Path;
+use uuid::Uuid;
+
+/// The number of automatic snapshots kept for each collection, older
+/// automatic snapshots are removed when new ones are taken. Snapshots
+/// taken by the user are never removed automatically.
+const MAX_AUTOMATIC_SNAPSHOTS: usize = 10;
+
+/// A point-in-time record of a [`Collection`]'s plugins, including their enabled
+/// states. A copy of the config directory is kept in [`paths::snapshot_dir`].
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    pub id: i64,
+    pub collection_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub label: String,
+    pub automatic: bool,
+    pub plugins: Vec<Plugin>,
+}
+
+/// Records the current state of the [`Collection`] and copies its config directory,
+/// returns the new snapshot's id.
+pub async fn take(db: &Db, collection: &Collection, label: &str, automatic: bool) -> Result<i64> {
+    let snapshot_id = db.save_snapshot(collection, label, automatic).await?;
+
+    let config_dir = collection.game.mod_loader.config_dir();
+    let src = paths::collection_dir(&collection.id).join(config_dir);
+    let dest = paths::snapshot_dir(&collection.id, snapshot_id).join(config_dir);
+
+    if let Err(err) = copy_config_dir(&src, &dest).await {
+        db.remove_snapshot(snapshot_id).await?;
+
+        return Err(err.wrap_err("failed to copy config directory into snapshot"));
+    }
+
+    if automatic {
+        prune(db, collection).await?;
+    }
+
+    tracing::info!("took snapshot {} of collection '{}' ({})", snapshot_id, collection.name, label);
+
+    Ok(snapshot_id)
+}
+
+/// Takes an automatic snapshot before the collection is modified.
+pub async fn take_automatic(db: &Db, collection: &Collection, label: &str) -> Result<i64> {
+    take(db, collection, label, true).await
+}
+
+/// Restores the [`Collection`] to the state recorded in the [`Snapshot`]. Plugins
+/// are reinstalled from the cache, downloading them if required, and the config
+/// directory is replaced with the snapshot's copy. Does not save the collection.
+pub async fn restore(collection: &mut Collection, snapshot: &Snapshot) -> Result<()> {
+    ensure!(
+        snapshot.collection_id == collection.id,
+        "snapshot {} does not belong to collection '{}'",
+        snapshot.id,
+        collection.name
+    );
+
+    let mut removed = Vec::new();
+
+    // Remove everything that isn't the exact version recorded in the snapshot.
+    for index in (0..collection.plugins.len()).rev() {
+        let ident = collection.plugins[index].ident();
+
+        if !snapshot.plugins.iter().any(|x| x.ident() == ident) {
+            removed.push(super::install::uninstall_plugin(collection, index).await?);
+        }
+    }
+
+    let missing_plugins = snapshot.plugins
+        .iter()
+        .filter(|plugin| !collection.plugins.iter().any(|x| x.ident() == plugin.ident()))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    downloader::install_plugins(collection, &missing_plugins).await?;
+
+    // Newly installed plugins are enabled on disk until switched.
+    for plugin in &snapshot.plugins {
+        let enabled_on_disk = collection.plugins
+            .iter()
+            .find(|x| x.ident() == plugin.ident())
+            .is_none_or(|x| x.enabled);
+
+        if enabled_on_disk != plugin.enabled {
+            let installer = collection.game
+                .mod_loader
+                .installer_for_plugin(plugin.ident().full_name());
+
+            installer.switch(plugin.enabled, plugin, collection).await?;
+        }
+    }
+
+    collection.plugins = snapshot.plugins.clone();
+
+    // Only config files outside the config directory are kept by the merge, the
+    // config directory is replaced with the snapshot's copy below.
+    super::install::merge_replaced_defaults(collection, &removed).await?;
+
+    let config_dir = collection.game.mod_loader.config_dir();
+    let collection_config_dir = paths::collection_dir(&collection.id).join(config_dir);
+
+    if collection_config_dir.exists() {
+        tokio::fs::remove_dir_all(&collection_config_dir).await?;
+    }
+
+    copy_config_dir(
+        &paths::snapshot_dir(&collection.id, snapshot.id).join(config_dir),
+        &collection_config_dir,
+    ).await?;
+
+    Ok(())
+}
+
+/// Removes a single snapshot and its copy of the config directory.
+pub async fn remove(db: &Db, snapshot: &Snapshot) -> Result<()> {
+    let snapshot_dir = paths::snapshot_dir(&snapshot.collection_id, snapshot.id);
+
+    if snapshot_dir.exists() {
+        tokio::fs::remove_dir_all(&snapshot_dir).await?;
+    }
+
+    db.remove_snapshot(snapshot.id).await
+}
+
+/// Removes every snapshot belonging to the [`Collection`].
+pub async fn remove_all(db: &Db, collection: &Collection) -> Result<()> {
+    let snapshots_dir = paths::collection_snapshots_dir(&collection.id);
+
+    if snapshots_dir.exists() {
+        tokio::fs::remove_dir_all(&snapshots_dir).await?;
+    }
+
+    db.remove_snapshots(&collection.id).await
+}
+
+/// Removes the oldest automatic snapshots past [`MAX_AUTOMATIC_SNAPSHOTS`].
+async fn prune(db: &Db, collection: &Collection) -> Result<()> {
+    let expired = db
+        .load_snapshots(&collection.id)
+        .await?
+        .into_iter()
+        .filter(|snapshot| snapshot.automatic)
+        .skip(MAX_AUTOMATIC_SNAPSHOTS);
+
+    for snapshot in expired {
+        remove(db, &snapshot).await?;
+    }
+
+    Ok(())
+}
+
+async fn copy_config_dir(src: &Path, dest: &Path) -> Result<()> {
+    tokio::fs::create_dir_all(dest).await?;
+
+    if !src.exists() {
+        return Ok(());
+    }
+
+    fs::copy_dir_contents_to(src, dest.to_path_buf(), |_, _| {
+        Ok(fs::CopyFileOpts { should_copy_file: true, should_overwrite_file: true })
+    }).await
+}
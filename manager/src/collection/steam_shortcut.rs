@@ -0,0 +1,426 @@
+//! Adds collections to Steam's library as non-Steam game shortcuts, so they can be
+//! launched from Steam, such as in the Steam Deck's game mode.
+
+use std::path::{Path, PathBuf};
+use eyre::{ensure, Result};
+use crate::collection::Collection;
+use crate::platforms::{self, SteamArtwork};
+use crate::utils::vdf::{self, BinaryMap, BinaryValue};
+
+const SHORTCUTS_KEY: &str = "shortcuts";
+
+/// Steam keeps this string on every shortcut but only uses it for devkit builds,
+/// so it records which collection a shortcut launches. Unlike the app id it stays
+/// the same when the collection is renamed.
+const COLLECTION_ID_KEY: &str = "DevkitGameID";
+
+/// The artwork Steam looks for in the `grid` directory, by the suffix of its name.
+const GRID_NAMES: &[(SteamArtwork, &str)] = &[
+    (SteamArtwork::Capsule, "p"),
+    (SteamArtwork::Header, ""),
+    (SteamArtwork::Hero, "_hero"),
+    (SteamArtwork::Logo, "_logo"),
+];
+
+/// What a shortcut in `shortcuts.vdf` launches.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SteamShortcut {
+    pub name: String,
+    /// The executable, quoted as Steam stores it.
+    pub exe: String,
+    pub start_dir: String,
+    pub launch_options: String,
+    pub icon: String,
+    pub collection_id: String,
+}
+
+impl SteamShortcut {
+    pub fn new(collection: &Collection, exe_path: &Path) -> Self {
+        let start_dir = exe_path.parent().unwrap_or(exe_path);
+
+        let icon = collection.game.platforms.steam
+            .as_ref()
+            .and_then(|steam| platforms::steam_artwork(steam.id, SteamArtwork::Icon))
+            .map(|x| x.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        Self {
+            name: format!("KB - {} - {}", collection.game.name, collection.name),
+            exe: format!("\"{}\"", exe_path.display()),
+            start_dir: format!("\"{}\"", start_dir.display()),
+            launch_options: format!("launch \"{}\"", collection.name.replace('"', "\\\"")),
+            icon,
+            collection_id: collection.id.to_string(),
+        }
+    }
+
+    /// The id Steam gives a non-Steam game, derived from its executable and name.
+    /// Steam uses it to name the shortcut's artwork.
+    pub fn app_id(&self) -> u32 {
+        let mut hasher = crc32fast::Hasher::new();
+
+        hasher.update(self.exe.as_bytes());
+        hasher.update(self.name.as_bytes());
+
+        hasher.finalize() | 0x80000000
+    }
+}
+
+/// Adds the [`Collection`] to the current Steam user's shortcuts, or updates its
+/// shortcut if it was already added, and copies the game's artwork for it. Steam
+/// must not be running, as it overwrites the shortcuts when it exits. Returns the
+/// shortcut's app id.
+pub async fn add(collection: &Collection) -> Result<u32> {
+    ensure_steam_closed()?;
+
+    let user_dir = platforms::steam_user_dir()?;
+    let mut shortcuts = read_shortcuts(&user_dir).await?;
+
+    let shortcut = SteamShortcut::new(collection, &std::env::current_exe()?);
+    let previous_app_id = find_app_id(&shortcuts, &shortcut.collection_id);
+
+    upsert(&mut shortcuts, &shortcut);
+
+    write_shortcuts(&user_dir, &shortcuts).await?;
+
+    let grid_dir = user_dir.join("config").join("grid");
+
+    // The app id depends on the name, so a renamed shortcut leaves artwork behind.
+    if let Some(previous_app_id) = previous_app_id
+        && previous_app_id != shortcut.app_id()
+    {
+        remove_artwork(previous_app_id, &grid_dir).await?;
+    }
+
+    if let Some(steam) = &collection.game.platforms.steam {
+        copy_artwork(steam.id, shortcut.app_id(), &grid_dir).await?;
+    }
+
+    Ok(shortcut.app_id())
+}
+
+/// Updates the [`Collection`]'s shortcut after it was renamed, as the shortcut
+/// launches it by name. Returns whether the collection had a shortcut.
+pub async fn rename(collection: &Collection) -> Result<bool> {
+    if !has_shortcut(collection).await? {
+        return Ok(false);
+    }
+
+    add(collection).await?;
+
+    Ok(true)
+}
+
+/// Removes the [`Collection`]'s shortcut and its artwork. Steam must not be
+/// running. Returns whether the collection had a shortcut.
+pub async fn remove(collection: &Collection) -> Result<bool> {
+    if !has_shortcut(collection).await? {
+        return Ok(false);
+    }
+
+    ensure_steam_closed()?;
+
+    let user_dir = platforms::steam_user_dir()?;
+    let mut shortcuts = read_shortcuts(&user_dir).await?;
+
+    let Some(app_id) = remove_entry(&mut shortcuts, &collection.id.to_string()) else {
+        return Ok(false);
+    };
+
+    write_shortcuts(&user_dir, &shortcuts).await?;
+    remove_artwork(app_id, &user_dir.join("config").join("grid")).await?;
+
+    Ok(true)
+}
+
+/// Whether the current Steam user has a shortcut for the [`Collection`]. Without a
+/// Steam install there are no shortcuts.
+pub async fn has_shortcut(collection: &Collection) -> Result<bool> {
+    let Ok(user_dir) = platforms::steam_user_dir() else {
+        return Ok(false);
+    };
+
+    let shortcuts = read_shortcuts(&user_dir).await?;
+
+    Ok(find_app_id(&shortcuts, &collection.id.to_string()).is_some())
+}
+
+/// Fails when Steam is running, as it writes its own copy of the shortcuts back
+/// when it exits.
+pub fn ensure_steam_closed() -> Result<()> {
+    ensure!(
+        !platforms::is_steam_running(),
+        "Steam must be closed before changing its shortcuts, otherwise it discards the changes when it exits"
+    );
+
+    Ok(())
+}
+
+fn shortcuts_path(user_dir: &Path) -> PathBuf {
+    user_dir.join("config").join("shortcuts.vdf")
+}
+
+async fn read_shortcuts(user_dir: &Path) -> Result<BinaryMap> {
+    let shortcuts_path = shortcuts_path(user_dir);
+
+    match shortcuts_path.exists() {
+        true => vdf::parse(&tokio::fs::read(&shortcuts_path).await?),
+        false => Ok(Vec::new()),
+    }
+}
+
+async fn write_shortcuts(user_dir: &Path, shortcuts: &BinaryMap) -> Result<()> {
+    let shortcuts_path = shortcuts_path(user_dir);
+
+    tokio::fs::create_dir_all(shortcuts_path.parent().unwrap()).await?;
+    tokio::fs::write(&shortcuts_path, vdf::render(shortcuts)).await?;
+
+    Ok(())
+}
+
+/// Returns the app id of the shortcut for the collection with the id.
+fn find_app_id(shortcuts: &BinaryMap, collection_id: &str) -> Option<u32> {
+    vdf::get(shortcuts, SHORTCUTS_KEY)
+        .and_then(BinaryValue::as_map)?
+        .iter()
+        .filter_map(|(_, entry)| entry.as_map())
+        .find(|entry| is_collection_entry(entry, collection_id))
+        .and_then(|entry| vdf::get(entry, "appid"))
+        .and_then(BinaryValue::as_int)
+}
+
+/// Removes the shortcut for the collection with the id from the contents of a
+/// `shortcuts.vdf`. Returns the removed shortcut's app id.
+pub fn remove_entry(shortcuts: &mut BinaryMap, collection_id: &str) -> Option<u32> {
+    let entries = shortcuts
+        .iter_mut()
+        .find(|(key, _)| key.eq_ignore_ascii_case(SHORTCUTS_KEY))
+        .and_then(|(_, value)| value.as_map_mut())?;
+
+    let index = entries
+        .iter()
+        .position(|(_, entry)| entry.as_map().is_some_and(|entry| is_collection_entry(entry, collection_id)))?;
+
+    let (_, entry) = entries.remove(index);
+
+    entry.as_map().and_then(|entry| vdf::get(entry, "appid")).and_then(BinaryValue::as_int)
+}
+
+fn is_collection_entry(entry: &BinaryMap, collection_id: &str) -> bool {
+    vdf::get(entry, COLLECTION_ID_KEY) == Some(&BinaryValue::String(collection_id.to_owned()))
+}
+
+/// Adds the shortcut to the contents of a `shortcuts.vdf`, replacing an existing
+/// entry for the same collection or with the same app id. Returns whether a new
+/// entry was added.
+pub fn upsert(shortcuts: &mut BinaryMap, shortcut: &SteamShortcut) -> bool {
+    if vdf::get(shortcuts, SHORTCUTS_KEY).and_then(BinaryValue::as_map).is_none() {
+        vdf::set(shortcuts, SHORTCUTS_KEY, BinaryValue::Map(Vec::new()));
+    }
+
+    let entries = shortcuts
+        .iter_mut()
+        .find(|(key, _)| key.eq_ignore_ascii_case(SHORTCUTS_KEY))
+        .and_then(|(_, value)| value.as_map_mut())
+        .unwrap();
+
+    let app_id = shortcut.app_id();
+
+    let existing = entries
+        .iter_mut()
+        .filter_map(|(_, entry)| entry.as_map_mut())
+        .find(|entry| {
+            is_collection_entry(entry, &shortcut.collection_id)
+                || vdf::get(entry, "appid").and_then(BinaryValue::as_int) == Some(app_id)
+        });
+
+    let is_new = existing.is_none();
+
+    let entry = match existing {
+        Some(entry) => entry,
+        None => {
+            // Entries are keyed by their index, which can have gaps when Steam
+            // removed a shortcut, so continue from the highest one.
+            let key = entries
+                .iter()
+                .filter_map(|(key, _)| key.parse::<usize>().ok())
+                .max()
+                .map_or(0, |x| x + 1);
+
+            entries.push((key.to_string(), BinaryValue::Map(Vec::new())));
+            entries.last_mut().and_then(|(_, entry)| entry.as_map_mut()).unwrap()
+        }
+    };
+
+    vdf::set(entry, "appid", BinaryValue::Int(app_id));
+    vdf::set(entry, "AppName", BinaryValue::String(shortcut.name.clone()));
+    vdf::set(entry, "Exe", BinaryValue::String(shortcut.exe.clone()));
+    vdf::set(entry, "StartDir", BinaryValue::String(shortcut.start_dir.clone()));
+    vdf::set(entry, "icon", BinaryValue::String(shortcut.icon.clone()));
+    vdf::set(entry, "LaunchOptions", BinaryValue::String(shortcut.launch_options.clone()));
+    vdf::set(entry, COLLECTION_ID_KEY, BinaryValue::String(shortcut.collection_id.clone()));
+
+    if is_new {
+        vdf::set(entry, "AllowDesktopConfig", BinaryValue::Int(1));
+        vdf::set(entry, "AllowOverlay", BinaryValue::Int(1));
+        vdf::set(entry, "tags", BinaryValue::Map(Vec::new()));
+    }
+
+    is_new
+}
+
+/// Copies the game's artwork from Steam's library cache to the names Steam looks
+/// for a shortcut's custom artwork under.
+async fn copy_artwork(game_app_id: u32, shortcut_app_id: u32, grid_dir: &Path) -> Result<()> {
+    for (artwork, suffix) in GRID_NAMES {
+        let Some(src_path) = platforms::steam_artwork(game_app_id, *artwork) else {
+            continue;
+        };
+
+        let extension = src_path.extension().unwrap_or_default().to_string_lossy();
+
+        tokio::fs::create_dir_all(grid_dir).await?;
+        tokio::fs::copy(&src_path, grid_dir.join(format!("{}{}.{}", shortcut_app_id, suffix, extension))).await?;
+    }
+
+    Ok(())
+}
+
+/// Removes the artwork copied for a shortcut by [`copy_artwork`].
+async fn remove_artwork(shortcut_app_id: u32, grid_dir: &Path) -> Result<()> {
+    if !grid_dir.exists() {
+        return Ok(());
+    }
+
+    let stems = GRID_NAMES
+        .iter()
+        .map(|(_, suffix)| format!("{}{}", shortcut_app_id, suffix))
+        .collect::<Vec<_>>();
+
+    let mut entries = tokio::fs::read_dir(grid_dir).await?;
+
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+
+        if stems.iter().any(|x| *x == stem) {
+            tokio::fs::remove_file(&path).await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shortcut() -> SteamShortcut {
+        SteamShortcut {
+            name: "KB - Valheim - Example".to_owned(),
+            exe: "\"/opt/katabasis/katabasis\"".to_owned(),
+            start_dir: "\"/opt/katabasis\"".to_owned(),
+            launch_options: "launch \"Example\"".to_owned(),
+            icon: String::new(),
+            collection_id: "00000000-0000-0000-0000-000000000001".to_owned(),
+        }
+    }
+
+    fn entries(shortcuts: &BinaryMap) -> &BinaryMap {
+        vdf::get(shortcuts, SHORTCUTS_KEY).and_then(BinaryValue::as_map).unwrap()
+    }
+
+    #[test]
+    fn add_shortcut_once() {
+        let mut shortcuts = vdf::parse(include_bytes!("../../fixtures/steam/shortcuts.vdf")).unwrap();
+
+        assert!(upsert(&mut shortcuts, &shortcut()));
+
+        let rendered = vdf::render(&shortcuts);
+
+        assert!(!upsert(&mut shortcuts, &shortcut()));
+        assert_eq!(vdf::render(&shortcuts), rendered);
+
+        let entries = entries(&shortcuts);
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].0, "1");
+
+        let entry = entries[1].1.as_map().unwrap();
+
+        assert_eq!(vdf::get(entry, "appid").and_then(BinaryValue::as_int), Some(shortcut().app_id()));
+        assert_eq!(vdf::get(entry, "LaunchOptions"), Some(&BinaryValue::String("launch \"Example\"".to_owned())));
+    }
+
+    #[test]
+    fn update_existing_shortcut() {
+        let mut shortcuts = vdf::parse(include_bytes!("../../fixtures/steam/shortcuts_empty.vdf")).unwrap();
+
+        upsert(&mut shortcuts, &shortcut());
+
+        let mut icon_shortcut = shortcut();
+        icon_shortcut.icon = "/home/user/icon.jpg".to_owned();
+
+        assert!(!upsert(&mut shortcuts, &icon_shortcut));
+
+        let entries = entries(&shortcuts);
+        let entry = entries[0].1.as_map().unwrap();
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(vdf::get(entry, "icon"), Some(&BinaryValue::String("/home/user/icon.jpg".to_owned())));
+    }
+
+    #[test]
+    fn rename_and_remove_shortcut() {
+        let mut shortcuts = vdf::parse(include_bytes!("../../fixtures/steam/shortcuts.vdf")).unwrap();
+
+        upsert(&mut shortcuts, &shortcut());
+
+        let renamed = SteamShortcut {
+            name: "KB - Valheim - Renamed".to_owned(),
+            launch_options: "launch \"Renamed\"".to_owned(),
+            ..shortcut()
+        };
+
+        assert_ne!(renamed.app_id(), shortcut().app_id());
+        assert!(!upsert(&mut shortcuts, &renamed));
+        assert_eq!(entries(&shortcuts).len(), 2);
+        assert_eq!(find_app_id(&shortcuts, &renamed.collection_id), Some(renamed.app_id()));
+
+        assert_eq!(remove_entry(&mut shortcuts, &renamed.collection_id), Some(renamed.app_id()));
+        assert_eq!(remove_entry(&mut shortcuts, &renamed.collection_id), None);
+
+        let entries = entries(&shortcuts);
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(
+            vdf::get(entries[0].1.as_map().unwrap(), "AppName"),
+            Some(&BinaryValue::String("Heroic Games Launcher".to_owned()))
+        );
+    }
+
+    #[test]
+    fn add_shortcut_after_gap() {
+        let mut shortcuts = vdf::parse(include_bytes!("../../fixtures/steam/shortcuts.vdf")).unwrap();
+
+        let existing = shortcuts
+            .iter_mut()
+            .find_map(|(_, value)| value.as_map_mut())
+            .unwrap();
+
+        // Only keep an entry keyed 2, as if the ones before it were removed.
+        existing[0].0 = "2".to_owned();
+
+        assert!(upsert(&mut shortcuts, &shortcut()));
+
+        let keys = entries(&shortcuts).iter().map(|(key, _)| key.as_str()).collect::<Vec<_>>();
+
+        assert_eq!(keys, ["2", "3"]);
+    }
+
+    #[test]
+    fn shortcut_app_id() {
+        assert!(shortcut().app_id() & 0x80000000 != 0);
+        assert_ne!(shortcut().app_id(), SteamShortcut { name: "Other".to_owned(), ..shortcut() }.app_id());
+    }
+}
@@ -1,13 +1,18 @@
-use std::{str::FromStr, time::Duration};
+use std::{collections::HashMap, str::FromStr, time::Duration};
 
-use eyre::{eyre, Context, OptionExt, Result};
+use eyre::{ensure, eyre, Context, OptionExt, Result};
 use sqlx::{
     Pool, Sqlite,
     migrate::MigrateDatabase,
     sqlite::{SqliteConnectOptions, SqlitePoolOptions},
 };
 
-use crate::{collection::Collection, targets, utils};
+use crate::{
+    collection::{bisect::Bisect, session::PlayStats, snapshot::Snapshot, Collection},
+    targets, utils,
+};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
 
 pub struct Db(Pool<Sqlite>);
 
@@ -68,16 +73,20 @@ impl Db {
     pub async fn save_collection(&self, collection: &Collection) -> Result<()> {
         self.with_transaction(async |tx| {
             let json_plugins = serde_json::to_string(&collection.plugins)?;
+            let json_launch_options = serde_json::to_string(&collection.launch_options)?;
+            let collection_id = collection.id.to_string();
 
             sqlx::query!(
                 "INSERT OR REPLACE INTO collections (
-                    name, plugins, game
+                    name, id, plugins, game, launch_options
                 ) VALUES (
-                    $1, $2, $3
+                    $1, $2, $3, $4, $5
                 )",
                 collection.name,
+                collection_id,
                 json_plugins,
-                collection.game.slug
+                collection.game.slug,
+                json_launch_options
             )
             .execute(&mut **tx)
             .await?;
@@ -90,20 +99,22 @@ impl Db {
     }
 
     pub async fn load_collection(&self, id: &str) -> Result<Collection> {
-        let record = sqlx::query!("SELECT name, plugins, game FROM collections WHERE name = $1", id)
+        let record = sqlx::query!("SELECT name, id, plugins, game, launch_options FROM collections WHERE name = $1", id)
             .fetch_one(&self.0)
             .await?;
 
         Ok(Collection {
             name: record.name,
+            id: record.id.parse()?,
             plugins: serde_json::from_str(&record.plugins)?,
+            launch_options: serde_json::from_str(&record.launch_options)?,
             game: targets::from_slug(&record.game).ok_or_else(||
                 eyre!("Slug '{}' does not match any supported games", &record.game))?,
         })
     }
 
     pub async fn load_all_collections(&self) -> Result<Vec<Collection>> {
-        let collections = sqlx::query!("SELECT name, plugins, game FROM collections")
+        let collections = sqlx::query!("SELECT name, id, plugins, game, launch_options FROM collections")
             .fetch_all(&self.0)
             .await?;
 
@@ -115,8 +126,10 @@ impl Db {
 
                 Ok(Collection {
                     name: row.name,
+                    id: row.id.parse()?,
                     plugins,
                     game,
+                    launch_options: serde_json::from_str(&row.launch_options)?,
                 })
             })
             .collect::<Result<Vec<_>, _>>()?;
@@ -124,11 +137,216 @@ impl Db {
         Ok(collections)
     }
 
+    /// Returns whether a [`Collection`] with the provided name exists.
+    pub async fn has_collection(&self, name: &str) -> Result<bool> {
+        let record = sqlx::query!("SELECT name FROM collections WHERE name = $1", name)
+            .fetch_optional(&self.0)
+            .await?;
+
+        Ok(record.is_some())
+    }
+
+    /// Renames a [`Collection`] within a single transaction, failing if the new
+    /// name is already taken.
+    pub async fn rename_collection(&self, old_name: &str, new_name: &str) -> Result<()> {
+        self.with_transaction(async |tx| {
+            let existing = sqlx::query!("SELECT name FROM collections WHERE name = $1", new_name)
+                .fetch_optional(&mut **tx)
+                .await?;
+
+            ensure!(existing.is_none(), "a collection named '{}' already exists", new_name);
+
+            let result = sqlx::query!("UPDATE collections SET name = $1 WHERE name = $2", new_name, old_name)
+                .execute(&mut **tx)
+                .await?;
+
+            ensure!(result.rows_affected() == 1, "collection '{}' does not exist", old_name);
+
+            Ok(())
+        })
+        .await
+    }
+
     pub async fn remove_collection(&self, collection: &Collection) -> Result<()> {
         sqlx::query!("DELETE FROM collections WHERE name = $1", collection.name).execute(&self.0).await?;
 
         Ok(())
     }
+
+    /// Saves the current plugins of the [`Collection`] as a new snapshot, returns
+    /// the id of the snapshot.
+    pub async fn save_snapshot(&self, collection: &Collection, label: &str, automatic: bool) -> Result<i64> {
+        let collection_id = collection.id.to_string();
+        let created_at = Utc::now().to_rfc3339();
+        let json_plugins = serde_json::to_string(&collection.plugins)?;
+
+        let result = sqlx::query!(
+            "INSERT INTO snapshots (
+                collection_id, created_at, label, automatic, plugins
+            ) VALUES (
+                $1, $2, $3, $4, $5
+            )",
+            collection_id,
+            created_at,
+            label,
+            automatic,
+            json_plugins
+        )
+        .execute(&self.0)
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    pub async fn load_snapshot(&self, id: i64) -> Result<Snapshot> {
+        let record = sqlx::query!(
+            "SELECT id, collection_id, created_at, label, automatic, plugins FROM snapshots WHERE id = $1",
+            id
+        )
+        .fetch_one(&self.0)
+        .await
+        .with_context(|| format!("failed to find snapshot {}", id))?;
+
+        Ok(Snapshot {
+            id: record.id,
+            collection_id: record.collection_id.parse()?,
+            created_at: DateTime::parse_from_rfc3339(&record.created_at)?.to_utc(),
+            label: record.label,
+            automatic: record.automatic,
+            plugins: serde_json::from_str(&record.plugins)?,
+        })
+    }
+
+    /// Loads every snapshot of a collection, newest first.
+    pub async fn load_snapshots(&self, collection_id: &Uuid) -> Result<Vec<Snapshot>> {
+        let collection_id = collection_id.to_string();
+
+        let snapshots = sqlx::query!(
+            r#"SELECT id AS "id!", collection_id, created_at, label, automatic, plugins FROM snapshots
+            WHERE collection_id = $1 ORDER BY id DESC"#,
+            collection_id
+        )
+        .fetch_all(&self.0)
+        .await?;
+
+        snapshots
+            .into_iter()
+            .map(|row| -> Result<Snapshot> {
+                Ok(Snapshot {
+                    id: row.id,
+                    collection_id: row.collection_id.parse()?,
+                    created_at: DateTime::parse_from_rfc3339(&row.created_at)?.to_utc(),
+                    label: row.label,
+                    automatic: row.automatic,
+                    plugins: serde_json::from_str(&row.plugins)?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn remove_snapshot(&self, id: i64) -> Result<()> {
+        sqlx::query!("DELETE FROM snapshots WHERE id = $1", id).execute(&self.0).await?;
+
+        Ok(())
+    }
+
+    pub async fn remove_snapshots(&self, collection_id: &Uuid) -> Result<()> {
+        let collection_id = collection_id.to_string();
+
+        sqlx::query!("DELETE FROM snapshots WHERE collection_id = $1", collection_id)
+            .execute(&self.0)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Adds a finished session's play time to the collection's total and marks it
+    /// as the last time the collection was played.
+    pub async fn record_play_session(
+        &self,
+        collection_id: &Uuid,
+        started_at: DateTime<Utc>,
+        play_time_secs: i64,
+    ) -> Result<()> {
+        let collection_id = collection_id.to_string();
+        let started_at = started_at.to_rfc3339();
+
+        sqlx::query!(
+            "INSERT INTO play_stats (collection_id, play_time, last_played) VALUES ($1, $2, $3)
+            ON CONFLICT (collection_id) DO UPDATE SET
+                play_time = play_time + excluded.play_time,
+                last_played = excluded.last_played",
+            collection_id,
+            play_time_secs,
+            started_at
+        )
+        .execute(&self.0)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Returns the play stats of every collection that has been played.
+    pub async fn load_play_stats(&self) -> Result<HashMap<Uuid, PlayStats>> {
+        let rows = sqlx::query!("SELECT collection_id, play_time, last_played FROM play_stats")
+            .fetch_all(&self.0)
+            .await?;
+
+        rows.into_iter()
+            .map(|row| -> Result<(Uuid, PlayStats)> {
+                Ok((row.collection_id.parse()?, PlayStats {
+                    play_time_secs: row.play_time,
+                    last_played: Some(DateTime::parse_from_rfc3339(&row.last_played)?.to_utc()),
+                }))
+            })
+            .collect()
+    }
+
+    /// Saves the state of an in-progress [`Bisect`], replacing any previous state.
+    pub async fn save_bisect(&self, bisect: &Bisect) -> Result<()> {
+        let collection_id = bisect.collection_id.to_string();
+        let state = serde_json::to_string(bisect)?;
+
+        sqlx::query!(
+            "INSERT OR REPLACE INTO bisect_sessions (collection_id, state) VALUES ($1, $2)",
+            collection_id,
+            state
+        )
+        .execute(&self.0)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn load_bisect(&self, collection_id: &Uuid) -> Result<Option<Bisect>> {
+        let collection_id = collection_id.to_string();
+
+        let record = sqlx::query!("SELECT state FROM bisect_sessions WHERE collection_id = $1", collection_id)
+            .fetch_optional(&self.0)
+            .await?;
+
+        record.map(|x| Ok(serde_json::from_str(&x.state)?)).transpose()
+    }
+
+    pub async fn remove_bisect(&self, collection_id: &Uuid) -> Result<()> {
+        let collection_id = collection_id.to_string();
+
+        sqlx::query!("DELETE FROM bisect_sessions WHERE collection_id = $1", collection_id)
+            .execute(&self.0)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn remove_play_stats(&self, collection_id: &Uuid) -> Result<()> {
+        let collection_id = collection_id.to_string();
+
+        sqlx::query!("DELETE FROM play_stats WHERE collection_id = $1", collection_id)
+            .execute(&self.0)
+            .await?;
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -138,7 +356,7 @@ mod tests {
     async fn create_in_memory_database() -> Db {
         let db_uri = "sqlite::memory:";
 
-        let sqlite_opts = SqliteConnectOptions::from_str(&db_uri)
+        let sqlite_opts = SqliteConnectOptions::from_str(db_uri)
             .unwrap()
             .busy_timeout(Duration::from_secs(30))
             .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal)
@@ -162,8 +380,10 @@ mod tests {
 
         let collection = Collection {
             name: "EXAMPLE".to_owned(),
+            id: uuid::Uuid::new_v4(),
             game: targets::from_slug("valheim").unwrap(),
             plugins: vec![],
+            launch_options: Default::default(),
         };
 
         assert!(db.save_collection(&collection).await.is_ok());
@@ -172,5 +392,78 @@ mod tests {
 
         assert_eq!(save_data.len(), 1);
         assert_eq!(save_data[0].name, "EXAMPLE");
+        assert_eq!(save_data[0].id, collection.id);
+        assert!(db.has_collection("EXAMPLE").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn rename_collection() {
+        let db = create_in_memory_database().await;
+
+        for name in ["EXAMPLE", "TAKEN"] {
+            let collection = Collection {
+                name: name.to_owned(),
+                id: uuid::Uuid::new_v4(),
+                game: targets::from_slug("valheim").unwrap(),
+                plugins: vec![],
+                launch_options: Default::default(),
+            };
+
+            db.save_collection(&collection).await.unwrap();
+        }
+
+        assert!(db.rename_collection("EXAMPLE", "TAKEN").await.is_err());
+        assert!(db.rename_collection("MISSING", "OTHER").await.is_err());
+
+        db.rename_collection("EXAMPLE", "RENAMED").await.unwrap();
+
+        assert!(db.load_collection("EXAMPLE").await.is_err());
+        assert_eq!(db.load_collection("RENAMED").await.unwrap().name, "RENAMED");
+    }
+
+    #[tokio::test]
+    async fn save_and_load_snapshots() {
+        let db = create_in_memory_database().await;
+
+        let collection = Collection {
+            name: "EXAMPLE".to_owned(),
+            id: uuid::Uuid::new_v4(),
+            game: targets::from_slug("valheim").unwrap(),
+            plugins: vec![],
+            launch_options: Default::default(),
+        };
+
+        let first = db.save_snapshot(&collection, "first", true).await.unwrap();
+        let second = db.save_snapshot(&collection, "second", false).await.unwrap();
+
+        let snapshots = db.load_snapshots(&collection.id).await.unwrap();
+
+        assert_eq!(snapshots.iter().map(|x| x.id).collect::<Vec<_>>(), vec![second, first]);
+        assert!(snapshots[1].automatic);
+        assert_eq!(db.load_snapshot(second).await.unwrap().label, "second");
+
+        db.remove_snapshots(&collection.id).await.unwrap();
+
+        assert!(db.load_snapshots(&collection.id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn record_play_sessions() {
+        let db = create_in_memory_database().await;
+        let collection_id = uuid::Uuid::new_v4();
+
+        let first = DateTime::parse_from_rfc3339("2026-10-18T12:00:00Z").unwrap().to_utc();
+        let second = DateTime::parse_from_rfc3339("2026-10-19T12:00:00Z").unwrap().to_utc();
+
+        db.record_play_session(&collection_id, first, 60).await.unwrap();
+        db.record_play_session(&collection_id, second, 30).await.unwrap();
+
+        let stats = db.load_play_stats().await.unwrap();
+
+        assert_eq!(stats[&collection_id], PlayStats { play_time_secs: 90, last_played: Some(second) });
+
+        db.remove_play_stats(&collection_id).await.unwrap();
+
+        assert!(db.load_play_stats().await.unwrap().is_empty());
     }
 }
@@ -2,14 +2,18 @@ use dashmap::DashMap;
 use eyre::{eyre, OptionExt, Result};
 use serde::Serialize;
 use std::sync::Arc;
+use std::fmt;
 use std::time::Duration;
-use tokio::sync::OnceCell;
+use tokio::sync::{broadcast, OnceCell};
 use uuid::Uuid;
 
 static EVENT_STATE: OnceCell<Arc<EventState>> = OnceCell::const_new();
 
+const EVENT_CHANNEL_CAPACITY: usize = 64;
+
 pub struct EventState {
     progress_bars: DashMap<Uuid, ProgressBar>,
+    events: broadcast::Sender<Event>,
 }
 
 impl EventState {
@@ -18,6 +22,7 @@ impl EventState {
             .get_or_try_init(|| async {
                 Ok(Arc::new(Self {
                     progress_bars: DashMap::new(),
+                    events: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
                 }))
             })
             .await
@@ -32,6 +37,86 @@ impl EventState {
     }
 }
 
+/// Something that happened during an operation that the user should know about.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Event {
+    /// A config value the user changed was also changed by a plugin update, the
+    /// user's value has been kept.
+    #[serde(rename_all = "camelCase")]
+    ConfigConflict {
+        collection: String,
+        plugin: String,
+        file: String,
+        section: String,
+        key: String,
+        user_value: String,
+        default_value: String,
+    },
+    /// A supervised launch found the game process. `pid` is the launcher's when
+    /// the game process couldn't be identified.
+    GameStarted {
+        collection: String,
+        pid: Option<u32>,
+    },
+    /// The game process of a supervised launch exited.
+    #[serde(rename_all = "camelCase")]
+    GameExited {
+        collection: String,
+        exit_code: Option<i32>,
+        play_time_secs: i64,
+    },
+    /// The game has to be launched with a launch option set in the platform's
+    /// client, such as the DLL override Proton needs for mods to load or the
+    /// collection's own environment and wrapper.
+    #[serde(rename_all = "camelCase")]
+    LaunchOptionRequired {
+        collection: String,
+        launch_option: String,
+    },
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::ConfigConflict { plugin, file, section, key, user_value, default_value, .. } => write!(
+                f,
+                "{} changed the default of [{}] {} in {} to '{}', keeping your value '{}'",
+                plugin, section, key, file, default_value, user_value
+            ),
+            Event::GameStarted { collection, .. } => write!(f, "{} started", collection),
+            Event::GameExited { collection, exit_code: Some(exit_code), .. } => {
+                write!(f, "{} exited with code {}", collection, exit_code)
+            }
+            Event::GameExited { collection, .. } => write!(f, "{} exited", collection),
+            Event::LaunchOptionRequired { collection, launch_option } => write!(
+                f,
+                "{} needs the launch option '{}' added to the game in its launcher",
+                collection, launch_option
+            ),
+        }
+    }
+}
+
+/// Subscribes to every [`Event`] emitted after this call.
+pub fn subscribe() -> Result<broadcast::Receiver<Event>> {
+    Ok(EventState::get()?.events.subscribe())
+}
+
+pub fn emit(event: Event) -> Result<()> {
+    let state = EventState::get()?;
+
+    match event {
+        Event::ConfigConflict { .. } | Event::LaunchOptionRequired { .. } => tracing::warn!("{}", event),
+        _ => tracing::info!("{}", event),
+    }
+
+    // Sending only fails when nobody is subscribed, which is fine.
+    let _ = state.events.send(event);
+
+    Ok(())
+}
+
 #[cfg(feature = "cli")]
 const CLI_PROGRESS_TOTAL: u64 = 1000;
 
@@ -55,11 +140,11 @@ impl Drop for ProgressBarId {
         let progress_uuid = self.0;
 
         tokio::spawn(async move {
-            if let Ok(state) = EventState::get() {
-                if let Some((_, bar)) = state.progress_bars.remove(&progress_uuid) {
-                    #[cfg(feature = "cli")]
-                    bar.cli_progress_bar.finish();
-                }
+            if let Ok(state) = EventState::get()
+                && let Some((_, bar)) = state.progress_bars.remove(&progress_uuid)
+            {
+                #[cfg(feature = "cli")]
+                bar.cli_progress_bar.finish();
             }
         });
     }
@@ -0,0 +1,172 @@
+//! Games installed through Lutris, which keeps a YAML config for each game named
+//! after its slug and when it was installed, e.g. `valheim-1700000000.yml`.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use eyre::{OptionExt, Result};
+use serde::Deserialize;
+use tokio::process::Command;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct GameConfig {
+    game: GameSection,
+    system: SystemSection,
+    wine: WineSection,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct GameSection {
+    exe: Option<PathBuf>,
+    prefix: Option<PathBuf>,
+    working_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct SystemSection {
+    env: HashMap<String, serde_yaml::Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct WineSection {
+    dll_overrides: HashMap<String, serde_yaml::Value>,
+}
+
+/// Returns the directories Lutris keeps game configs in that exist. Newer versions
+/// use the data directory, older ones and Flatpak installs may use others.
+pub fn games_dirs() -> Vec<PathBuf> {
+    if !cfg!(target_os = "linux") {
+        return Vec::new();
+    }
+
+    let flatpak_dir = dirs_next::home_dir().map(|x| x.join(".var/app/net.lutris.Lutris"));
+
+    [
+        dirs_next::data_dir().map(|x| x.join("lutris/games")),
+        dirs_next::config_dir().map(|x| x.join("lutris/games")),
+        flatpak_dir.as_ref().map(|x| x.join("data/lutris/games")),
+        flatpak_dir.as_ref().map(|x| x.join("config/lutris/games")),
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|x| x.is_dir())
+    .collect()
+}
+
+/// Finds the directory of the game with the slug from its most recently installed
+/// config, based on the game's executable.
+pub fn game_dir(games_dirs: &[PathBuf], slug: &str) -> Result<PathBuf> {
+    let config = find_config(games_dirs, slug)?;
+
+    let exe = config.game.exe.ok_or_eyre(format!("the Lutris config for {} has no executable", slug))?;
+
+    // Relative executables are resolved the way Lutris does, against the working
+    // directory or else the Wine prefix.
+    let exe = match config.game.working_dir.or(config.game.prefix) {
+        Some(base_dir) if exe.is_relative() => base_dir.join(exe),
+        _ => exe,
+    };
+
+    exe.parent()
+        .map(Path::to_path_buf)
+        .ok_or_eyre(format!("invalid executable {} for {}", exe.display(), slug))
+}
+
+/// Whether the game's Lutris config sets the DLL override Proton needs, either as
+/// an environment variable or in its Wine settings.
+pub fn has_dll_override(games_dirs: &[PathBuf], slug: &str) -> bool {
+    let Ok(config) = find_config(games_dirs, slug) else {
+        return false;
+    };
+
+    let in_env = config.system.env
+        .get("WINEDLLOVERRIDES")
+        .and_then(|x| x.as_str())
+        .is_some_and(|x| x.contains("winhttp=n"));
+
+    let in_wine = config.wine.dll_overrides
+        .get("winhttp")
+        .and_then(|x| x.as_str())
+        .is_some_and(|x| x.starts_with('n'));
+
+    in_env || in_wine
+}
+
+pub fn launch_command(slug: &str) -> Command {
+    let url = format!("lutris:rungame/{}", slug);
+
+    match super::find_in_path("lutris") {
+        Some(lutris) => {
+            let mut command = Command::new(lutris);
+            command.arg(url);
+
+            command
+        }
+        None => super::open_url_command(&url),
+    }
+}
+
+fn find_config(games_dirs: &[PathBuf], slug: &str) -> Result<GameConfig> {
+    let mut configs = Vec::new();
+
+    for games_dir in games_dirs {
+        let Ok(entries) = games_dir.read_dir() else {
+            continue;
+        };
+
+        for entry in entries.filter_map(Result::ok) {
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+
+            // Checking the install time rules out other games whose slug starts
+            // with this one, e.g. `valheim-dedicated-server`.
+            let installed_at = file_name
+                .strip_suffix(".yml")
+                .and_then(|x| x.strip_prefix(slug))
+                .and_then(|x| x.strip_prefix('-'))
+                .and_then(|x| x.parse::<u64>().ok());
+
+            if let Some(installed_at) = installed_at {
+                configs.push((installed_at, entry.path()));
+            }
+        }
+    }
+
+    let (_, config_path) = configs
+        .into_iter()
+        .max()
+        .ok_or_eyre(format!("{} is not installed through Lutris", slug))?;
+
+    Ok(serde_yaml::from_str(&std::fs::read_to_string(config_path)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture_dirs() -> Vec<PathBuf> {
+        vec![Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/lutris/games")]
+    }
+
+    #[test]
+    fn find_installed_games() {
+        assert_eq!(
+            game_dir(&fixture_dirs(), "valheim").unwrap(),
+            Path::new("/games/Lutris/valheim/drive_c/Games/Valheim")
+        );
+        assert_eq!(
+            game_dir(&fixture_dirs(), "lethal-company").unwrap(),
+            Path::new("/games/Lutris/lethal-company/drive_c/Games/Lethal Company")
+        );
+
+        assert!(game_dir(&fixture_dirs(), "repo").is_err());
+    }
+
+    #[test]
+    fn read_dll_override() {
+        assert!(has_dll_override(&fixture_dirs(), "valheim"));
+        assert!(!has_dll_override(&fixture_dirs(), "lethal-company"));
+    }
+}
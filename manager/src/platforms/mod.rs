@@ -0,0 +1,432 @@
+use std::path::{Path, PathBuf};
+
+use eyre::{eyre, OptionExt, Result, bail};
+use keyvalues_parser::{Value, Vdf};
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System};
+use tokio::process::Command;
+
+use crate::targets::{Platform, SteamPlatform, Target};
+
+mod lutris;
+
+/// The DLL override Proton needs to load Doorstop's `winhttp.dll` proxy from the
+/// game directory instead of its own builtin version.
+pub const WINE_DLL_OVERRIDES: &str = "winhttp=n,b";
+
+/// Works out the correct launch command for the provided [`Target`] and [`Platform`].
+pub fn launch_command(target: Target, platform: Platform) -> Option<Command> {
+    let platforms = &target.platforms;
+
+    match platform {
+        Platform::Steam => steam_command(target).ok(),
+        Platform::Lutris => Some(lutris::launch_command(platforms.lutris.as_ref()?.slug)),
+    }
+}
+
+/// Works out the directory of the game based on the provided [`Platform`].
+pub fn game_dir(target: Target, platform: Platform) -> Result<PathBuf> {
+    let platforms = &target.platforms;
+    let unsupported = || eyre!("target {} does not support {:?}", target.name, platform);
+
+    match platform {
+        Platform::Steam => steam_game_dir(target),
+        Platform::Lutris => {
+            let lutris = platforms.lutris.as_ref().ok_or_else(unsupported)?;
+
+            lutris::game_dir(&lutris::games_dirs(), lutris.slug)
+        }
+    }
+}
+
+fn steam_command(target: Target) -> Result<Command> {
+    let Some(steam) = &target.platforms.steam else {
+        bail!("Target {} is not available on Steam", target.name);
+    };
+
+    let Some(steam_exe) = find_steam_exe() else {
+        // Steam installed through Flatpak or Snap has no binary we can run, but it
+        // still handles `steam://` URLs.
+        if cfg!(target_os = "linux") && steamlocate::SteamDir::locate().is_ok() {
+            return Ok(steam_url_command(steam));
+        }
+
+        bail!("Steam executable not found, game should be launched directly");
+    };
+
+    let mut command = Command::new(steam_exe);
+    command.arg("-applaunch").arg(steam.id.to_string());
+
+    Ok(command)
+}
+
+fn find_steam_exe() -> Option<PathBuf> {
+    let steam_dir = steamlocate::SteamDir::locate().ok()?;
+
+    let steam_exe = if cfg!(windows) {
+        Some(steam_dir.path().join("steam.exe"))
+    }
+    else {
+        find_in_path("steam").or_else(|| Some(steam_dir.path().join("steam.sh")))
+    };
+
+    let steam_exe = steam_exe.filter(|x| x.exists());
+
+    if steam_exe.is_none() {
+        tracing::warn!(
+            "Steam executable not found, it is likely that another platform is being used"
+        );
+    }
+
+    steam_exe
+}
+
+fn find_in_path(name: &str) -> Option<PathBuf> {
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Launches the game through whichever program handles `steam://` URLs. The game's
+/// arguments have to be part of the URL, see [`with_url_args`].
+fn steam_url_command(steam: &SteamPlatform) -> Command {
+    open_url_command(&format!("steam://rungameid/{}", steam.id))
+}
+
+/// Opens the URL with the program registered for its scheme.
+fn open_url_command(url: &str) -> Command {
+    let mut command = match cfg!(windows) {
+        true => Command::new("explorer"),
+        false => Command::new("xdg-open"),
+    };
+
+    command.arg(url);
+
+    command
+}
+
+const LAUNCHER_URL_SCHEMES: &[&str] = &["steam://", "lutris:"];
+
+/// URL handlers only take the URL, so any arguments added after a launcher URL
+/// are moved into it for `steam://` URLs, after the double slash Steam expects
+/// them behind, and dropped for other launchers. Other commands are returned
+/// unchanged.
+pub fn with_url_args(command: Command) -> Command {
+    let std_command = command.as_std();
+    let mut args = std_command.get_args().map(|x| x.to_string_lossy());
+
+    let Some(url) = args.next().filter(|x| LAUNCHER_URL_SCHEMES.iter().any(|scheme| x.starts_with(scheme))) else {
+        return command;
+    };
+
+    let game_args = args
+        .map(|arg| match arg.contains(' ') {
+            true => format!("\"{}\"", arg),
+            false => arg.into_owned(),
+        })
+        .collect::<Vec<_>>();
+
+    if game_args.is_empty() {
+        return command;
+    }
+
+    let url = match url.starts_with("steam://") {
+        true => format!("{}//{}", url, encode_url_component(&game_args.join(" "))),
+        false => {
+            tracing::warn!("arguments can't be passed through {}, set them in the launcher instead", url);
+
+            url.into_owned()
+        }
+    };
+
+    let mut url_command = Command::new(std_command.get_program());
+
+    url_command
+        .arg(url)
+        .envs(std_command.get_envs().filter_map(|(key, value)| Some((key, value?))));
+
+    url_command
+}
+
+fn encode_url_component(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Returns the launch option the user has to add to the game on the [`Platform`]
+/// for mods to load under Proton, or `None` when it is already set. Steam doesn't
+/// pass the environment of an already running client on to the games it starts,
+/// so the DLL override has to be part of the game's own launch options.
+pub fn missing_proton_launch_option(target: Target, platform: Platform) -> Option<String> {
+    match platform {
+        Platform::Steam => {
+            let steam = target.platforms.steam.as_ref()?;
+
+            let launch_options = steam_launch_options(steam.id).unwrap_or_else(|err| {
+                tracing::warn!("failed to read Steam launch options: {}", err);
+
+                Vec::new()
+            });
+
+            let is_set = launch_options
+                .iter()
+                .any(|x| x.contains("WINEDLLOVERRIDES") && x.contains("winhttp=n"));
+
+            (!is_set).then(|| format!("WINEDLLOVERRIDES=\"{}\" %command%", WINE_DLL_OVERRIDES))
+        }
+        // Lutris takes environment variables in its game settings.
+        Platform::Lutris => {
+            let slug = target.platforms.lutris.as_ref()?.slug;
+
+            (!lutris::has_dll_override(&lutris::games_dirs(), slug))
+                .then(|| format!("WINEDLLOVERRIDES={}", WINE_DLL_OVERRIDES))
+        }
+    }
+}
+
+/// Reads the launch options every Steam user on this machine has set for the app.
+fn steam_launch_options(app_id: u32) -> Result<Vec<String>> {
+    let userdata_dir = steamlocate::SteamDir::locate()?.path().join("userdata");
+
+    let mut launch_options = Vec::new();
+
+    for entry in userdata_dir.read_dir()?.filter_map(Result::ok) {
+        let config_path = entry.path().join("config").join("localconfig.vdf");
+
+        if let Ok(contents) = std::fs::read_to_string(&config_path) {
+            launch_options.extend(parse_launch_options(&contents, app_id)?);
+        }
+    }
+
+    Ok(launch_options)
+}
+
+/// Finds the app's launch options in the contents of a `localconfig.vdf`.
+fn parse_launch_options(contents: &str, app_id: u32) -> Result<Option<String>> {
+    let local_config = Vdf::parse(contents)?;
+
+    let launch_options = ["Software", "Valve", "Steam", "apps", &app_id.to_string(), "LaunchOptions"]
+        .iter()
+        .try_fold(&local_config.value, |value, key| get_ignore_case(value, key))
+        .and_then(Value::get_str)
+        .map(str::to_owned);
+
+    Ok(launch_options)
+}
+
+/// Steam isn't consistent with the case of its keys, e.g. both `apps` and `Apps`
+/// are used.
+fn get_ignore_case<'a>(value: &'a Value<'a>, key: &str) -> Option<&'a Value<'a>> {
+    value
+        .get_obj()?
+        .iter()
+        .find(|(x, _)| x.eq_ignore_ascii_case(key))
+        .and_then(|(_, values)| values.first())
+}
+
+/// Artwork Steam caches for the games in its library.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SteamArtwork {
+    Icon,
+    /// The tall cover shown in the library grid.
+    Capsule,
+    /// The wide banner shown in recent games.
+    Header,
+    /// The background at the top of the game's library page.
+    Hero,
+    Logo,
+}
+
+impl SteamArtwork {
+    fn file_names(&self) -> &'static [&'static str] {
+        match self {
+            SteamArtwork::Icon => &["icon.jpg"],
+            SteamArtwork::Capsule => &["library_600x900.jpg"],
+            SteamArtwork::Header => &["header.jpg"],
+            SteamArtwork::Hero => &["library_hero.jpg"],
+            SteamArtwork::Logo => &["logo.png"],
+        }
+    }
+}
+
+/// Finds the artwork for an app in Steam's library cache. Older clients name
+/// the files after the app, newer ones put them in a directory per app.
+pub fn steam_artwork(app_id: u32, artwork: SteamArtwork) -> Option<PathBuf> {
+    let cache_dir = steamlocate::SteamDir::locate()
+        .ok()?
+        .path()
+        .join("appcache")
+        .join("librarycache");
+
+    artwork
+        .file_names()
+        .iter()
+        .flat_map(|name| {
+            [
+                cache_dir.join(format!("{}_{}", app_id, name)),
+                cache_dir.join(app_id.to_string()).join(name),
+            ]
+        })
+        .find(|path| path.is_file())
+}
+
+/// The difference between a 64-bit Steam id and the account id used for the
+/// user's `userdata` directory.
+const STEAM_ID_OFFSET: u64 = 76561197960265728;
+
+/// Returns the `userdata` directory of the Steam user who logged in most recently.
+pub fn steam_user_dir() -> Result<PathBuf> {
+    let steam_dir = steamlocate::SteamDir::locate()?;
+    let login_users = std::fs::read_to_string(steam_dir.path().join("config").join("loginusers.vdf"))?;
+
+    let steam_id = most_recent_steam_user(&login_users)?.ok_or_eyre("no Steam user has logged in")?;
+
+    let account_id = steam_id
+        .checked_sub(STEAM_ID_OFFSET)
+        .ok_or_else(|| eyre!("invalid Steam id {} in loginusers.vdf", steam_id))?;
+
+    Ok(steam_dir.path().join("userdata").join(account_id.to_string()))
+}
+
+/// Whether the Steam client is running. Steam writes its copy of files such as
+/// `shortcuts.vdf` back when it exits, discarding any changes made meanwhile.
+pub fn is_steam_running() -> bool {
+    let mut system = System::new();
+
+    system.refresh_processes_specifics(ProcessesToUpdate::All, true, ProcessRefreshKind::nothing());
+
+    system.processes().values().any(|process| {
+        let name = process.name().to_string_lossy();
+
+        name.eq_ignore_ascii_case("steam") || name.eq_ignore_ascii_case("steam.exe")
+    })
+}
+
+fn most_recent_steam_user(login_users: &str) -> Result<Option<u64>> {
+    let login_users = Vdf::parse(login_users)?;
+
+    let Some(users) = login_users.value.get_obj() else {
+        return Ok(None);
+    };
+
+    let users = users
+        .iter()
+        .filter_map(|(id, values)| Some((id.parse::<u64>().ok()?, values.first()?)))
+        .collect::<Vec<_>>();
+
+    let most_recent = users
+        .iter()
+        .find(|(_, user)| get_ignore_case(user, "MostRecent").and_then(Value::get_str) == Some("1"))
+        .or(users.first())
+        .map(|(id, _)| *id);
+
+    Ok(most_recent)
+}
+
+/// Returns the path Wine maps a path on the host to, through the `Z:` drive.
+pub fn to_wine_path(path: &Path) -> String {
+    format!("Z:{}", path.to_string_lossy().replace('/', "\\"))
+}
+
+fn steam_game_dir(target: Target) -> Result<PathBuf> {
+    let Some(steam) = &target.platforms.steam else {
+        bail!("target {} does not support Steam", target.name);
+    };
+
+    let steam_dir = steamlocate::SteamDir::locate()?;
+
+    let (app, lib) = steam_dir.find_app(steam.id)?.ok_or_eyre(format!(
+        "failed to find app {} in Steam library",
+        target.name
+    ))?;
+
+    Ok(lib.resolve_app_dir(&app))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCAL_CONFIG: &str = r#"
+"UserLocalConfigStore"
+{
+    "Software"
+    {
+        "Valve"
+        {
+            "Steam"
+            {
+                "Apps"
+                {
+                    "892970"
+                    {
+                        "LastPlayed"        "1760000000"
+                        "LaunchOptions"     "WINEDLLOVERRIDES=\"winhttp=n,b\" %command% -console"
+                    }
+                    "1966720"
+                    {
+                        "LastPlayed"        "1760000000"
+                    }
+                }
+            }
+        }
+    }
+}
+"#;
+
+    #[test]
+    fn read_steam_launch_options() {
+        assert_eq!(
+            parse_launch_options(LOCAL_CONFIG, 892970).unwrap().as_deref(),
+            Some("WINEDLLOVERRIDES=\"winhttp=n,b\" %command% -console")
+        );
+        assert_eq!(parse_launch_options(LOCAL_CONFIG, 1966720).unwrap(), None);
+    }
+
+    #[test]
+    fn find_most_recent_steam_user() {
+        let login_users = r#"
+"users"
+{
+    "76561198000000001"
+    {
+        "AccountName"       "first"
+        "MostRecent"        "0"
+    }
+    "76561198000000002"
+    {
+        "AccountName"       "second"
+        "MostRecent"        "1"
+    }
+}
+"#;
+
+        assert_eq!(most_recent_steam_user(login_users).unwrap(), Some(76561198000000002));
+    }
+
+    #[test]
+    fn move_args_into_steam_url() {
+        let mut command = steam_url_command(&SteamPlatform { id: 1966720, dir_name: None });
+        command.args(["--doorstop-target", "Z:\\My Mods\\BepInEx.Preloader.dll"]);
+
+        let command = with_url_args(command);
+        let args = command.as_std().get_args().collect::<Vec<_>>();
+
+        assert_eq!(
+            args,
+            ["steam://rungameid/1966720//--doorstop-target%20%22Z%3A%5CMy%20Mods%5CBepInEx.Preloader.dll%22"]
+        );
+    }
+
+    #[test]
+    fn convert_to_wine_path() {
+        assert_eq!(
+            to_wine_path(Path::new("/home/user/BepInEx/core/BepInEx.Preloader.dll")),
+            "Z:\\home\\user\\BepInEx\\core\\BepInEx.Preloader.dll"
+        );
+    }
+}
@@ -44,6 +44,8 @@ impl AppState {
         }
 
         let (db, db_existed) = Db::init().await?;
+
+        crate::collection::move_legacy_dirs(&db).await?;
         let http = net::init()?;
 
         let app_state = Self {
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use std::fmt;
+use std::path::{Path, PathBuf};
 use std::sync::LazyLock;
 use strum::{EnumIter, IntoEnumIterator};
 use crate::collection::install::handler::bepinex::BepInExHandler;
@@ -33,6 +34,8 @@ struct JsonTarget<'a> {
     mod_loader: ModLoader<'a>,
     #[serde(borrow)]
     platforms: Platforms<'a>,
+    #[serde(borrow, default)]
+    linux: Option<LinuxBuild<'a>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -42,6 +45,8 @@ pub struct TargetData<'a> {
     pub slug: Cow<'a, str>,
     pub mod_loader: ModLoader<'a>,
     pub platforms: Platforms<'a>,
+    /// Only set for games with a native Linux build.
+    pub linux: Option<LinuxBuild<'a>>,
 }
 
 impl<'a> From<JsonTarget<'a>> for TargetData<'a> {
@@ -51,6 +56,7 @@ impl<'a> From<JsonTarget<'a>> for TargetData<'a> {
             slug,
             mod_loader,
             platforms,
+            linux,
         } = target;
 
         let slug = Cow::Borrowed(slug);
@@ -60,15 +66,28 @@ impl<'a> From<JsonTarget<'a>> for TargetData<'a> {
             slug,
             mod_loader,
             platforms,
+            linux,
         }
     }
 }
 
+/// What is needed to run a game's native Linux build with the mod loader.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinuxBuild<'a> {
+    /// The game's executable, relative to the game directory.
+    pub executable: &'a str,
+    /// Doorstop's library, relative to the collection directory, which is preloaded
+    /// into the game.
+    pub doorstop_library: &'a str,
+}
+
 #[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, EnumIter)]
 #[serde(rename_all = "camelCase")]
 pub enum Platform {
     #[default]
     Steam,
+    Lutris,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -79,11 +98,19 @@ pub struct SteamPlatform<'a> {
     pub dir_name: Option<&'a str>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LutrisPlatform<'a> {
+    pub slug: &'a str,
+}
+
 #[derive(Debug, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Platforms<'a> {
     #[serde(borrow)]
     pub steam: Option<SteamPlatform<'a>>,
+    #[serde(borrow, default)]
+    pub lutris: Option<LutrisPlatform<'a>>,
 }
 
 impl Platforms<'_> {
@@ -92,6 +119,7 @@ impl Platforms<'_> {
     pub fn has(&self, platform: Platform) -> bool {
         match platform {
             Platform::Steam => self.steam.is_some(),
+            Platform::Lutris => self.lutris.is_some(),
         }
     }
 
@@ -127,7 +155,8 @@ impl fmt::Display for ModLoader<'_> {
 }
 
 impl ModLoader<'_> {
-    fn is_loader_plugin(&self, name: &str) -> bool {
+    /// Returns whether the provided plugin name is the mod loader itself.
+    pub fn is_loader_plugin(&self, name: &str) -> bool {
         if let Some(plugin_name) = self.package_override {
             plugin_name == name
         }
@@ -139,19 +168,56 @@ impl ModLoader<'_> {
     }
 }
 
+const BEPINEX_MAPPED_DIRS: &[DirectoryMap] = &[
+    DirectoryMap::flattened("plugins", "BepInEx/plugins"),
+    DirectoryMap::flattened("core", "BepInEx/core"),
+    DirectoryMap::none("config", "BepInEx/config").files_mutable(),
+];
+
 impl ModLoader<'static> {
     pub fn installer_for_plugin(&'static self, plugin_name: &str) -> Box<dyn PluginHandler> {
         match (self.is_loader_plugin(plugin_name), &self.kind) {
             (true, ModLoaderKind::BepInEx) => Box::new(BepInExHandler),
-            (false, ModLoaderKind::BepInEx) => {
-                const MAPPED_DIRS: &[DirectoryMap] = &[
-                    DirectoryMap::flattened("plugins", "BepInEx/plugins"),
-                    DirectoryMap::flattened("core", "BepInEx/core"),
-                    DirectoryMap::none("config", "BepInEx/config").files_mutable(),
-                ];
-
-                Box::new(MappedInstaller::new(MAPPED_DIRS, 0))
-            },
+            (false, ModLoaderKind::BepInEx) => Box::new(MappedInstaller::new(BEPINEX_MAPPED_DIRS, 0)),
+        }
+    }
+
+    /// Returns the directories, relative to the collection directory, holding the
+    /// files that belong solely to the plugin.
+    pub fn plugin_dirs(&self, plugin_name: &str) -> Vec<PathBuf> {
+        match self.kind {
+            ModLoaderKind::BepInEx => MappedInstaller::new(BEPINEX_MAPPED_DIRS, 0).plugin_dirs(plugin_name),
+        }
+    }
+
+    /// Returns the top-level directories of the collection that are linked into
+    /// the game directory alongside its top-level files, such as the .NET runtime
+    /// used by BepInEx 6 IL2CPP.
+    pub fn linked_dirs(&self) -> &'static [&'static str] {
+        match self.kind {
+            ModLoaderKind::BepInEx => &["dotnet"],
+        }
+    }
+
+    /// Returns the directory, relative to the collection directory, that the mod
+    /// loader and its plugins store their configs in.
+    pub fn config_dir(&self) -> &'static Path {
+        match self.kind {
+            ModLoaderKind::BepInEx => Path::new("BepInEx/config"),
+        }
+    }
+
+    /// Returns whether a file, relative to the collection directory, can change after
+    /// it has been installed. These files are copied rather than hard linked so they
+    /// are never shared between the cache and collections.
+    pub fn is_mutable_file(&self, rel_path: &Path) -> bool {
+        match self.kind {
+            ModLoaderKind::BepInEx => {
+                BEPINEX_MAPPED_DIRS
+                    .iter()
+                    .any(|map| map.files_mutable && rel_path.starts_with(map.dir_path))
+                    || rel_path.extension().is_some_and(|ext| ext == "cfg" || ext == "log")
+            }
         }
     }
 }
@@ -169,5 +235,20 @@ mod tests {
         assert_eq!(valheim_data.mod_loader.kind, ModLoaderKind::BepInEx);
 
         assert!(valheim_data.platforms.has(Platform::Steam));
+        assert!(valheim_data.platforms.has(Platform::Lutris));
+        assert_eq!(valheim_data.linux.as_ref().unwrap().executable, "valheim.x86_64");
+
+        assert!(from_slug("lethal-company").unwrap().linux.is_none());
+    }
+
+    #[test]
+    fn bepinex_mutable_files() {
+        let mod_loader = &from_slug("valheim").unwrap().mod_loader;
+
+        assert!(mod_loader.is_mutable_file(Path::new("BepInEx/config/BepInEx.cfg")));
+        assert!(mod_loader.is_mutable_file(Path::new("BepInEx/config/Example/settings.json")));
+        assert!(mod_loader.is_mutable_file(Path::new("BepInEx/LogOutput.log")));
+        assert!(!mod_loader.is_mutable_file(Path::new("BepInEx/plugins/Example-Plugin/Plugin.dll")));
+        assert!(!mod_loader.is_mutable_file(Path::new("winhttp.dll")));
     }
 }
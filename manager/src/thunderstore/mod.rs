@@ -17,24 +17,37 @@ pub mod version;
 
 pub async fn query_latest_package(ident: &PackageIdent) -> Result<Package> {
     let state = AppState::get().await?;
-    let url = latest_package_url(&ident);
+    let url = latest_package_url(ident);
 
-    Ok(net::fetch_json(&url, state.http()).await?)
+    net::fetch_json(&url, state.http()).await
 }
 
+#[allow(dead_code)]
 pub async fn download_latest_package(ident: &PackageIdent) -> Result<(PluginZip, VersionIdent)> {
     let package = query_latest_package(ident).await?;
-    let zip = download_specific_package(&package.latest.ident).await?;
+    let zip = open_archive(download_specific_package(&package.latest.ident).await?)?;
 
     Ok((zip, package.latest.ident))
 }
 
-pub async fn download_specific_package(ident: &VersionIdent) -> Result<PluginZip> {
+pub async fn query_specific_package(ident: &VersionIdent) -> Result<PackageVersion> {
     let state = AppState::get().await?;
     let json_url = specific_package_url(ident);
 
-    let package_version: PackageVersion = net::fetch_json(&json_url, state.http()).await?;
-    let mut bytes_stream = net::fetch_stream(&package_version.download_url, state.http()).await?;
+    net::fetch_json(&json_url, state.http()).await
+}
+
+/// Downloads a specific package version, returning the raw archive bytes.
+pub async fn download_specific_package(ident: &VersionIdent) -> Result<Vec<u8>> {
+    let package_version = query_specific_package(ident).await?;
+
+    download_archive(&package_version.download_url).await
+}
+
+/// Downloads the archive at the provided URL into memory.
+pub async fn download_archive(url: &str) -> Result<Vec<u8>> {
+    let state = AppState::get().await?;
+    let mut bytes_stream = net::fetch_stream(url, state.http()).await?;
 
     let mut bytes: Vec<u8> = Vec::new();
 
@@ -43,6 +56,11 @@ pub async fn download_specific_package(ident: &VersionIdent) -> Result<PluginZip
         bytes.extend(chunk?);
     }
 
+    Ok(bytes)
+}
+
+/// Reads archive bytes into a [`PluginZip`] ready for extraction.
+pub fn open_archive(bytes: Vec<u8>) -> Result<PluginZip> {
     Ok(ZipArchive::new(Cursor::new(bytes))?)
 }
 
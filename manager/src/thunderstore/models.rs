@@ -23,6 +23,7 @@ pub struct PackageVersion {
     pub is_active: bool,
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PackageMetric {
     pub downloads: i64,
@@ -3,6 +3,7 @@ use std::ffi::OsStr;
 use std::io::{Cursor, Read};
 use std::path::{Path, PathBuf};
 use eyre::{Context, Result};
+use sha2::{Digest, Sha256};
 use tokio::fs::DirEntry;
 use zip::ZipArchive;
 
@@ -17,7 +18,7 @@ where
     M: FnMut(&Path) -> Result<Option<Cow<Path>>>,
 {
     for i in 0..archive.len() {
-        let file = archive.by_index(i)?;
+        let mut file = archive.by_index(i)?;
 
         if file.is_dir() {
             continue;
@@ -40,10 +41,7 @@ where
 
         let mut target_file = tokio::fs::File::create(&target_path).await?;
         let mut file_bytes = Vec::new();
-
-        for byte in file.bytes() {
-            file_bytes.push(byte?);
-        }
+        file.read_to_end(&mut file_bytes)?;
 
         tokio::io::copy(&mut file_bytes.as_slice(), &mut target_file).await?;
     }
@@ -51,6 +49,14 @@ where
     Ok(())
 }
 
+/// Returns the lowercase hex encoded SHA-256 hash of the provided bytes.
+pub fn hash_bytes(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
 pub async fn iterate_directory(path: impl Into<PathBuf>) -> Result<Vec<DirEntry>> {
     let mut paths: Vec<DirEntry> = vec![];
     let mut items = tokio::fs::read_dir(path.into()).await?;
@@ -144,11 +150,17 @@ where
 /// For example:
 /// - `if state == true file_name = file_name`
 /// - `if state != true file_name = file_name.DISABLED`
+///
+/// Files that are already in the requested state are left alone.
 pub async fn switch_file(path: impl AsRef<Path>, state: bool) -> Result<()> {
     let path = path.as_ref();
     let mut switched_path = path.to_path_buf();
 
-    if state {
+    if state != is_disabled_file(path) {
+        return Ok(());
+    }
+
+    if !state {
         add_extension(&mut switched_path, "DISABLED");
     }
     else {
@@ -162,6 +174,11 @@ pub async fn switch_file(path: impl AsRef<Path>, state: bool) -> Result<()> {
     Ok(())
 }
 
+/// Returns whether the file has been disabled by [`switch_file`].
+pub fn is_disabled_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "DISABLED")
+}
+
 fn add_extension(path: &mut PathBuf, extension: impl AsRef<OsStr>) {
     match path.extension() {
         Some(ext) => {
@@ -177,3 +194,40 @@ fn add_extension(path: &mut PathBuf, extension: impl AsRef<OsStr>) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_bytes_sha256() {
+        assert_eq!(
+            hash_bytes(b"katabasis"),
+            "f2ff8f50aa2da1ae78a856e2c4d0768f6f3cf614202dcda7821be09a1ad9caae"
+        );
+    }
+
+    #[tokio::test]
+    async fn switch_file_round_trip() {
+        let dir = std::env::temp_dir().join(format!("katabasis-switch-{}", uuid::Uuid::new_v4()));
+        let file = dir.join("Plugin.dll");
+        let disabled = dir.join("Plugin.dll.DISABLED");
+
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        tokio::fs::write(&file, b"").await.unwrap();
+
+        switch_file(&file, true).await.unwrap();
+        assert!(file.exists());
+
+        switch_file(&file, false).await.unwrap();
+        assert!(!file.exists() && disabled.exists());
+
+        switch_file(&disabled, false).await.unwrap();
+        assert!(disabled.exists());
+
+        switch_file(&disabled, true).await.unwrap();
+        assert!(file.exists() && !disabled.exists());
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+}
@@ -1,3 +1,4 @@
 pub mod net;
 pub mod paths;
 pub mod fs;
+pub mod vdf;
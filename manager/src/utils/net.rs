@@ -90,7 +90,7 @@ mod tests {
         assert_eq!(todo.user_id, 1);
         assert_eq!(todo.id, 1);
         assert_eq!(todo.title, "delectus aut autem");
-        assert_eq!(todo.completed, false);
+        assert!(!todo.completed);
     }
 
     #[tokio::test]
@@ -113,6 +113,6 @@ mod tests {
         assert_eq!(todo.user_id, 1);
         assert_eq!(todo.id, 1);
         assert_eq!(todo.title, "delectus aut autem");
-        assert_eq!(todo.completed, false);
+        assert!(!todo.completed);
     }
 }
@@ -1,6 +1,8 @@
 use crate::collection::Collection;
 use crate::targets::ModLoaderKind;
+use eyre::{OptionExt, Result};
 use std::path::PathBuf;
+use uuid::Uuid;
 
 const APP_GUID: &str = "dev.jackwhatley.katabasis";
 
@@ -30,22 +32,73 @@ pub fn sanitise_name(name: &str) -> String {
     )
 }
 
-/// Returns the path to a specific collection.
-pub fn collection_dir(id: &str) -> PathBuf {
+/// Returns the path to a specific collection based on its on-disk id.
+pub fn collection_dir(id: &Uuid) -> PathBuf {
     default_app_dir()
         .join("collections")
-        .join(sanitise_name(id))
+        .join(id.to_string())
+}
+
+/// Returns the path collections used before they were given an on-disk id,
+/// based on their sanitised name.
+pub fn legacy_collection_dir(name: &str) -> PathBuf {
+    default_app_dir()
+        .join("collections")
+        .join(sanitise_name(name))
 }
 
 /// Returns the path to a specific collection's plugins directory.
+#[allow(dead_code)]
 pub fn plugin_dir(collection: &Collection) -> PathBuf {
     match collection.game.mod_loader.kind {
-        ModLoaderKind::BepInEx => collection_dir(&collection.name)
+        ModLoaderKind::BepInEx => collection_dir(&collection.id)
             .join("BepInEx")
             .join("plugins"),
     }
 }
 
+/// Returns the path to a specific snapshot of a collection.
+pub fn snapshot_dir(collection_id: &Uuid, snapshot_id: i64) -> PathBuf {
+    collection_snapshots_dir(collection_id).join(snapshot_id.to_string())
+}
+
+/// Returns the path to the directory holding all of a collection's snapshots.
+pub fn collection_snapshots_dir(collection_id: &Uuid) -> PathBuf {
+    default_app_dir()
+        .join("snapshots")
+        .join(collection_id.to_string())
+}
+
+/// Returns the path to the directory holding the logs saved after each of a
+/// collection's supervised launches.
+pub fn session_logs_dir(collection_id: &Uuid) -> PathBuf {
+    default_app_dir()
+        .join("session_logs")
+        .join(collection_id.to_string())
+}
+
+/// Returns the path config files are backed up to before being reset to their defaults.
+pub fn config_backup_dir(collection_id: &Uuid, timestamp: &str) -> PathBuf {
+    default_app_dir()
+        .join("config_backups")
+        .join(collection_id.to_string())
+        .join(timestamp)
+}
+
+/// Returns the path to the record of files placed in a target's game directory.
+pub fn link_record_path(slug: &str) -> PathBuf {
+    default_app_dir()
+        .join("links")
+        .join(format!("{}.json", slug))
+}
+
+/// Returns the path game files replaced when linking are backed up to.
+pub fn game_backup_dir(slug: &str) -> PathBuf {
+    default_app_dir()
+        .join("links")
+        .join(format!("{}_backup", slug))
+}
+
 /// Returns the path to the app's directory for all cached data.
 pub fn cache_dir() -> PathBuf {
     default_app_dir().join("cache")
@@ -59,6 +112,13 @@ pub fn collection_export_path(name: &str) -> PathBuf {
     dirs_next::desktop_dir().unwrap().join(format!("{}.kbcollection", name))
 }
 
+/// Returns the path a [`Collection`]'s lockfile is written to on the desktop.
+pub fn collection_lockfile_path(name: &str) -> Result<PathBuf> {
+    let desktop_dir = dirs_next::desktop_dir().ok_or_eyre("failed to find the desktop directory")?;
+
+    Ok(desktop_dir.join(format!("{}.kblock", sanitise_name(name))))
+}
+
 pub fn log_path() -> PathBuf {
     default_app_dir().join("katabasis.log")
 }
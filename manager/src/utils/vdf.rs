@@ -0,0 +1,196 @@
+//! Steam's binary KeyValues format, used by files such as `shortcuts.vdf`.
+
+use eyre::{bail, eyre, Result};
+
+const TYPE_MAP: u8 = 0x00;
+const TYPE_STRING: u8 = 0x01;
+const TYPE_INT: u8 = 0x02;
+const TYPE_FLOAT: u8 = 0x03;
+const TYPE_UINT64: u8 = 0x07;
+const TYPE_MAP_END: u8 = 0x08;
+
+/// Entries keep the order they were read in, Steam relies on it for lists such as
+/// the shortcuts, which are maps keyed by their index.
+pub type BinaryMap = Vec<(String, BinaryValue)>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BinaryValue {
+    Map(BinaryMap),
+    String(String),
+    Int(u32),
+    Float(f32),
+    UInt64(u64),
+}
+
+impl BinaryValue {
+    pub fn as_map(&self) -> Option<&BinaryMap> {
+        match self {
+            BinaryValue::Map(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    pub fn as_map_mut(&mut self) -> Option<&mut BinaryMap> {
+        match self {
+            BinaryValue::Map(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<u32> {
+        match self {
+            BinaryValue::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the value of the first entry with the key, ignoring case as Steam
+/// isn't consistent about it.
+pub fn get<'a>(map: &'a BinaryMap, key: &str) -> Option<&'a BinaryValue> {
+    map.iter().find(|(x, _)| x.eq_ignore_ascii_case(key)).map(|(_, value)| value)
+}
+
+/// Sets the value of the entry with the key, adding it to the end when missing.
+pub fn set(map: &mut BinaryMap, key: &str, value: BinaryValue) {
+    match map.iter_mut().find(|(x, _)| x.eq_ignore_ascii_case(key)) {
+        Some((_, existing)) => *existing = value,
+        None => map.push((key.to_owned(), value)),
+    }
+}
+
+pub fn parse(bytes: &[u8]) -> Result<BinaryMap> {
+    let mut reader = Reader { bytes, position: 0 };
+
+    let map = reader.read_map()?;
+
+    if reader.position != bytes.len() {
+        bail!("unexpected data at byte {} of binary vdf", reader.position);
+    }
+
+    Ok(map)
+}
+
+pub fn render(map: &BinaryMap) -> Vec<u8> {
+    let mut bytes = Vec::new();
+
+    write_map(&mut bytes, map);
+
+    bytes
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl Reader<'_> {
+    fn read_map(&mut self) -> Result<BinaryMap> {
+        let mut map = Vec::new();
+
+        loop {
+            let value_type = self.take(1)?[0];
+
+            if value_type == TYPE_MAP_END {
+                return Ok(map);
+            }
+
+            let key = self.read_string()?;
+
+            let value = match value_type {
+                TYPE_MAP => BinaryValue::Map(self.read_map()?),
+                TYPE_STRING => BinaryValue::String(self.read_string()?),
+                TYPE_INT => BinaryValue::Int(u32::from_le_bytes(self.take(4)?.try_into()?)),
+                TYPE_FLOAT => BinaryValue::Float(f32::from_le_bytes(self.take(4)?.try_into()?)),
+                TYPE_UINT64 => BinaryValue::UInt64(u64::from_le_bytes(self.take(8)?.try_into()?)),
+                _ => bail!("unknown binary vdf type {:#04x} for key '{}'", value_type, key),
+            };
+
+            map.push((key, value));
+        }
+    }
+
+    fn read_string(&mut self) -> Result<String> {
+        let remaining = &self.bytes[self.position..];
+        let length = remaining
+            .iter()
+            .position(|x| *x == 0)
+            .ok_or_else(|| eyre!("unterminated string at byte {} of binary vdf", self.position))?;
+
+        let value = String::from_utf8_lossy(&remaining[..length]).into_owned();
+
+        self.position += length + 1;
+
+        Ok(value)
+    }
+
+    fn take(&mut self, count: usize) -> Result<&[u8]> {
+        let end = self.position + count;
+
+        if end > self.bytes.len() {
+            bail!("binary vdf ended unexpectedly");
+        }
+
+        let bytes = &self.bytes[self.position..end];
+
+        self.position = end;
+
+        Ok(bytes)
+    }
+}
+
+fn write_map(bytes: &mut Vec<u8>, map: &BinaryMap) {
+    for (key, value) in map {
+        let value_type = match value {
+            BinaryValue::Map(_) => TYPE_MAP,
+            BinaryValue::String(_) => TYPE_STRING,
+            BinaryValue::Int(_) => TYPE_INT,
+            BinaryValue::Float(_) => TYPE_FLOAT,
+            BinaryValue::UInt64(_) => TYPE_UINT64,
+        };
+
+        bytes.push(value_type);
+        write_string(bytes, key);
+
+        match value {
+            BinaryValue::Map(map) => write_map(bytes, map),
+            BinaryValue::String(value) => write_string(bytes, value),
+            BinaryValue::Int(value) => bytes.extend(value.to_le_bytes()),
+            BinaryValue::Float(value) => bytes.extend(value.to_le_bytes()),
+            BinaryValue::UInt64(value) => bytes.extend(value.to_le_bytes()),
+        }
+    }
+
+    bytes.push(TYPE_MAP_END);
+}
+
+fn write_string(bytes: &mut Vec<u8>, value: &str) {
+    bytes.extend(value.as_bytes());
+    bytes.push(0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_shortcuts() {
+        let bytes = include_bytes!("../../fixtures/steam/shortcuts.vdf");
+        let map = parse(bytes).unwrap();
+
+        let shortcuts = get(&map, "shortcuts").and_then(BinaryValue::as_map).unwrap();
+        let shortcut = shortcuts[0].1.as_map().unwrap();
+
+        assert_eq!(get(shortcut, "AppName"), Some(&BinaryValue::String("Heroic Games Launcher".to_owned())));
+        assert_eq!(get(shortcut, "appid").and_then(BinaryValue::as_int), Some(0xB2C4D1E7));
+
+        assert_eq!(render(&map), bytes);
+    }
+
+    #[test]
+    fn reject_truncated_vdf() {
+        let bytes = include_bytes!("../../fixtures/steam/shortcuts.vdf");
+
+        assert!(parse(&bytes[..bytes.len() - 1]).is_err());
+    }
+}